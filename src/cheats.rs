@@ -1,7 +1,9 @@
 use bevy::prelude::*;
 
+#[cfg(debug_assertions)]
+use crate::collision::collider::SpatialGrid;
 use crate::{
-    combat::health::DeathEvent,
+    combat::health::Health,
     experience::experience::{Experience, LevelUpEvent},
     player::Player,
     GameState,
@@ -11,17 +13,23 @@ pub struct CheatsPlugin;
 
 impl Plugin for CheatsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, (cheats).run_if(in_state(GameState::Playing)));
+        app.add_systems(Update, cheats.run_if(in_state(GameState::Playing)));
+
+        // Grid tuning is for development only, the collision benchmark lives with the collider tests
+        #[cfg(debug_assertions)]
+        app.add_systems(
+            Update,
+            tune_spatial_grid.run_if(in_state(GameState::Playing)),
+        );
     }
 }
 
 fn cheats(
-    q_player: Query<(Entity, &Player, &Experience)>,
+    mut q_player: Query<(&Player, &Experience, &mut Health)>,
     keyboard_input: Res<Input<KeyCode>>,
     mut levelup: EventWriter<LevelUpEvent>,
-    mut death_ev: EventWriter<DeathEvent>,
 ) {
-    let (player_entity, _player, xp) = q_player.single();
+    let (_player, xp, mut health) = q_player.single_mut();
 
     if keyboard_input.just_pressed(KeyCode::L) {
         levelup.send(LevelUpEvent {
@@ -29,9 +37,25 @@ fn cheats(
         });
     }
 
+    // Dies through the usual death checks
     if keyboard_input.just_pressed(KeyCode::K) {
-        death_ev.send(DeathEvent {
-            entity: player_entity,
-        });
+        health.value = 0;
     }
 }
+
+// Halves or doubles the spatial grid's cell size to find the best one for a scenario
+#[cfg(debug_assertions)]
+fn tune_spatial_grid(keyboard_input: Res<Input<KeyCode>>, mut spatial_grid: ResMut<SpatialGrid>) {
+    let cell_size = spatial_grid.cell_size();
+
+    let new_cell_size = if keyboard_input.just_pressed(KeyCode::BracketLeft) {
+        cell_size / 2.
+    } else if keyboard_input.just_pressed(KeyCode::BracketRight) {
+        cell_size * 2.
+    } else {
+        return;
+    };
+
+    spatial_grid.set_cell_size(new_cell_size);
+    info!("Spatial grid cell size: {}", new_cell_size);
+}
@@ -1,22 +1,33 @@
 use bevy::{prelude::*, utils::HashSet};
 
 pub mod collider;
+pub mod geometry;
+pub mod layers;
+pub mod solid;
+pub mod spatial_query;
 
 use self::collider::{
     collision_tick, CollisionEndEvent, CollisionStartEvent, IsCollidingEvent, PreviousCollisions,
+    SpatialGrid,
 };
+use self::solid::resolve_overlaps;
 use crate::GameState;
 
 pub struct CollisionPlugin;
 
 impl Plugin for CollisionPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, collision_tick.run_if(in_state(GameState::Playing)))
-            .add_event::<IsCollidingEvent>()
-            .add_event::<CollisionStartEvent>()
-            .add_event::<CollisionEndEvent>()
-            .insert_resource(PreviousCollisions {
-                collisions: HashSet::new(),
-            });
+        app.add_systems(
+            Update,
+            (collision_tick, resolve_overlaps.after(collision_tick))
+                .run_if(in_state(GameState::Playing)),
+        )
+        .add_event::<IsCollidingEvent>()
+        .add_event::<CollisionStartEvent>()
+        .add_event::<CollisionEndEvent>()
+        .insert_resource(PreviousCollisions {
+            collisions: HashSet::new(),
+        })
+        .init_resource::<SpatialGrid>();
     }
 }
@@ -1,24 +1,80 @@
-use bevy::{prelude::*, utils::HashSet};
+use bevy::{prelude::*, tasks::ComputeTaskPool, utils::HashSet};
 use std::collections::HashMap;
 
-type SpatialCoord = (i32, i32);
+use super::{
+    geometry::{cast_against_shape, cores_overlap, distance_to_shape, ShapeCore},
+    layers::{CollisionLayer, CollisionLayers},
+};
+
+pub type SpatialCoord = (i32, i32);
 
 #[derive(Component, Clone)]
 pub struct Collider {
     shape: ColliderShape,
-    spatial_coord: SpatialCoord,
+    layers: CollisionLayers,
 }
 
 #[derive(Clone)]
 pub enum ColliderShape {
     Rect(Vec2),
     Circle(f32),
+    Capsule { half_length: f32, radius: f32 },
+    Polygon(Vec<Vec2>),
+}
+
+/*
+Where a collider is in the world, the rotation is taken from the transform's z axis
+*/
+#[derive(Clone, Copy, Debug)]
+pub struct ColliderPose {
+    pub position: Vec2,
+    pub rotation: f32,
+}
+
+impl From<&Transform> for ColliderPose {
+    fn from(transform: &Transform) -> Self {
+        let (rotation, _, _) = transform.rotation.to_euler(EulerRot::ZYX);
+
+        ColliderPose {
+            position: transform.translation.truncate(),
+            rotation,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
 pub struct Collision {
     pub entity_a: Entity,
     pub entity_b: Entity,
+    // Fraction of the fast mover's last frame of movement at which the collision started,
+    // None if neither of the colliders is a fast mover
+    pub time_of_impact: Option<f32>,
+}
+
+/*
+Marks fast moving entities that could tunnel through colliders between two frames.
+Their path since the last collision tick is swept instead of only testing the end position
+*/
+#[derive(Component, Default)]
+pub struct ContinuousCollision {
+    previous_position: Option<Vec2>,
+    // Where the path swept by the last collision tick started
+    swept_from: Option<Vec2>,
+}
+
+impl ContinuousCollision {
+    // Moves the entity back to where it was at the time of impact on its last swept path,
+    // for reacting to a hit at the point of impact instead of wherever the entity ended up
+    pub fn rewind(&mut self, time_of_impact: f32, transform: &mut Transform) {
+        let Some(from) = self.swept_from else {
+            return;
+        };
+
+        let position = from.lerp(transform.translation.truncate(), time_of_impact);
+        transform.translation = position.extend(transform.translation.z);
+        // The next sweep starts from here, and not from the far side of whatever was hit
+        self.previous_position = Some(position);
+    }
 }
 
 #[derive(Event)]
@@ -36,236 +92,911 @@ pub struct CollisionEndEvent {
     pub collision: Collision,
 }
 
-// Checks if vector b is between a and c
-fn is_between(a: Vec2, b: Vec2, c: Vec2) -> bool {
-    ((a.x >= b.x && b.x >= c.x) && (a.y >= b.y && b.y >= c.y))
-        || ((a.x >= b.x && b.x >= c.x) && (c.y >= b.y && b.y >= a.y))
-        || ((c.x >= b.x && b.x >= a.x) && (a.y >= b.y && b.y >= c.y))
-        || ((c.x >= b.x && b.x >= a.x) && (c.y >= b.y && b.y >= a.y))
-}
-
 impl Collider {
-    pub fn new_rect(size: Vec2, position: Vec2) -> Collider {
-        Collider {
-            shape: ColliderShape::Rect(size),
-            spatial_coord: vec2_to_spatial_coord(position),
-        }
+    pub fn new_rect(size: Vec2) -> Collider {
+        Collider::new(ColliderShape::Rect(size))
+    }
+
+    pub fn new_circle(radius: f32) -> Collider {
+        Collider::new(ColliderShape::Circle(radius))
+    }
+
+    // A segment along the local x axis with rounded ends
+    pub fn new_capsule(half_length: f32, radius: f32) -> Collider {
+        Collider::new(ColliderShape::Capsule {
+            half_length,
+            radius,
+        })
     }
 
-    pub fn new_circle(radius: f32, position: Vec2) -> Collider {
+    // The vertices have to describe a convex polygon around the local origin
+    pub fn new_polygon(vertices: Vec<Vec2>) -> Collider {
+        debug_assert!(vertices.len() >= 3, "A polygon needs at least 3 vertices");
+        Collider::new(ColliderShape::Polygon(vertices))
+    }
+
+    fn new(shape: ColliderShape) -> Collider {
         Collider {
-            shape: ColliderShape::Circle(radius),
-            spatial_coord: vec2_to_spatial_coord(position),
+            shape,
+            layers: CollisionLayers::default(),
         }
     }
 
-    pub fn min_point(&self, position: Vec2) -> Vec2 {
-        match self.shape {
-            ColliderShape::Rect(size) => position - size / 2.,
-            ColliderShape::Circle(radius) => {
-                position
-                    - Vec2 {
-                        x: radius,
-                        y: radius,
-                    }
-            }
+    // Puts the collider on a single layer colliding with that layer's default mask
+    pub fn with_layer(self, layer: CollisionLayer) -> Collider {
+        self.with_layers(CollisionLayers::from_layer(layer))
+    }
+
+    pub fn with_layers(mut self, layers: CollisionLayers) -> Collider {
+        self.layers = layers;
+        self
+    }
+
+    // Only the geometry tests need the raw shape, everything else goes through the core
+    #[cfg(test)]
+    pub fn shape(&self) -> &ColliderShape {
+        &self.shape
+    }
+
+    pub fn layers(&self) -> CollisionLayers {
+        self.layers
+    }
+
+    // Largest distance from the center to any point of the shape, in any rotation
+    pub fn extent(&self) -> f32 {
+        match &self.shape {
+            ColliderShape::Rect(size) => size.length() / 2.,
+            ColliderShape::Circle(radius) => *radius,
+            ColliderShape::Capsule {
+                half_length,
+                radius,
+            } => half_length + radius,
+            ColliderShape::Polygon(vertices) => vertices
+                .iter()
+                .map(|vertex| vertex.length())
+                .fold(0., f32::max),
         }
     }
 
-    pub fn max_point(&self, position: Vec2) -> Vec2 {
+    pub fn core(&self, pose: ColliderPose) -> ShapeCore {
+        ShapeCore::new(&self.shape, pose.position, pose.rotation)
+    }
+
+    pub fn min_point(&self, pose: ColliderPose) -> Vec2 {
         match self.shape {
-            ColliderShape::Rect(size) => position + size / 2.,
-            ColliderShape::Circle(radius) => {
-                position
-                    + Vec2 {
-                        x: radius,
-                        y: radius,
-                    }
-            }
+            ColliderShape::Circle(radius) => pose.position - Vec2::splat(radius),
+            ColliderShape::Rect(size) if pose.rotation == 0. => pose.position - size / 2.,
+            _ => self.core(pose).min_point(),
         }
     }
 
-    pub fn contains_point(&self, my_position: Vec2, point: Vec2) -> bool {
+    pub fn max_point(&self, pose: ColliderPose) -> Vec2 {
         match self.shape {
-            ColliderShape::Rect(size) => {
-                point.x >= my_position.x - size.x / 2.
-                    && point.x <= my_position.x + size.x / 2.
-                    && point.y >= my_position.y - size.y / 2.
-                    && point.y <= my_position.y + size.y / 2.
-            }
-            ColliderShape::Circle(radius) => my_position.distance(point) <= radius,
+            ColliderShape::Circle(radius) => pose.position + Vec2::splat(radius),
+            ColliderShape::Rect(size) if pose.rotation == 0. => pose.position + size / 2.,
+            _ => self.core(pose).max_point(),
         }
     }
 
-    pub fn is_colliding(&self, position: Vec2, other: &Collider, other_position: Vec2) -> bool {
+    pub fn contains_point(&self, pose: ColliderPose, point: Vec2) -> bool {
+        distance_to_shape(point, &self.core(pose)) == 0.
+    }
+
+    // Touching shapes count as colliding
+    pub fn is_colliding(
+        &self,
+        pose: ColliderPose,
+        other: &Collider,
+        other_pose: ColliderPose,
+    ) -> bool {
         match (&self.shape, &other.shape) {
-            (ColliderShape::Rect(_), ColliderShape::Rect(_)) => {
-                // The shapes collide if the min or max point is between the min and max point of the other collider
-                is_between(
-                    self.max_point(position),
-                    other.max_point(other_position),
-                    self.min_point(position),
-                ) || is_between(
-                    self.max_point(position),
-                    other.min_point(other_position),
-                    self.min_point(position),
-                )
+            (ColliderShape::Circle(radius), ColliderShape::Circle(other_radius)) => {
+                pose.position.distance(other_pose.position) <= radius + other_radius
             }
-            (ColliderShape::Rect(size), ColliderShape::Circle(other_radius)) => {
-                Collider::is_colliding_rect_circle(*size, position, *other_radius, other_position)
+            (ColliderShape::Rect(size), ColliderShape::Rect(other_size))
+                if pose.rotation == 0. && other_pose.rotation == 0. =>
+            {
+                let distance = (pose.position - other_pose.position).abs();
+                let reach = (*size + *other_size) / 2.;
+                distance.x <= reach.x && distance.y <= reach.y
             }
-            (ColliderShape::Circle(radius), ColliderShape::Rect(other_size)) => {
-                Collider::is_colliding_rect_circle(*other_size, other_position, *radius, position)
+            (ColliderShape::Rect(size), ColliderShape::Circle(radius)) if pose.rotation == 0. => {
+                is_colliding_rect_circle(*size, pose.position, *radius, other_pose.position)
             }
-            (ColliderShape::Circle(radius), ColliderShape::Circle(other_radius)) => {
-                position.distance(other_position) < radius + other_radius
+            (ColliderShape::Circle(radius), ColliderShape::Rect(size))
+                if other_pose.rotation == 0. =>
+            {
+                is_colliding_rect_circle(*size, other_pose.position, *radius, pose.position)
             }
+            _ => cores_overlap(&self.core(pose), &other.core(other_pose)),
         }
     }
+}
 
-    fn is_colliding_rect_circle(size: Vec2, rect_pos: Vec2, radius: f32, circle_pos: Vec2) -> bool {
-        // Trivial case: if the center of the circle is in the rectangle there is a collision
-        if is_between(rect_pos + size / 2., circle_pos, rect_pos - size / 2.) {
-            true
-        // Otherwise, start with case where circle is to the left
-        } else if circle_pos.x < rect_pos.x {
-            (rect_pos.x - size.x / 2.) - circle_pos.x <= radius
-        // To the right
-        } else if circle_pos.x > rect_pos.x {
-            circle_pos.x - (rect_pos.x + size.x / 2.) <= radius
-        // Above
-        } else if circle_pos.y > rect_pos.y {
-            circle_pos.y - (rect_pos.y + size.y / 2.) <= radius
-        // Below
-        } else if circle_pos.y < rect_pos.y {
-            (rect_pos.y - size.y / 2.) - circle_pos.y <= radius
-        } else {
-            info!(
-                "This should not be possible! Size: {} RectPos: {} Radius: {} CirclePos: {}",
-                size, rect_pos, radius, circle_pos
-            );
+fn is_colliding_rect_circle(size: Vec2, rect_pos: Vec2, radius: f32, circle_pos: Vec2) -> bool {
+    // The closest point of the rect to the circle's center, including the corners
+    let closest = circle_pos.clamp(rect_pos - size / 2., rect_pos + size / 2.);
+    closest.distance(circle_pos) <= radius
+}
 
-            false
-        }
+// Pairs are always stored with the lower entity first so (a, b) and (b, a) share a key
+type CollisionPair = (Entity, Entity);
+
+fn ordered_pair(a: Entity, b: Entity) -> CollisionPair {
+    if a <= b {
+        (a, b)
+    } else {
+        (b, a)
     }
 }
 
-const SPATIAL_GRID_SIZE: f32 = 100.;
-
-pub fn vec3_to_spatial_coord(translation: Vec3) -> SpatialCoord {
-    let vec = translation.truncate() / SPATIAL_GRID_SIZE;
-    (vec.x.floor() as i32, vec.y.floor() as i32)
+#[derive(Resource)]
+pub struct PreviousCollisions {
+    pub collisions: HashSet<CollisionPair>,
 }
 
-pub fn vec2_to_spatial_coord(translation: Vec2) -> SpatialCoord {
-    let vec = translation / SPATIAL_GRID_SIZE;
-    (vec.x.floor() as i32, vec.y.floor() as i32)
+#[derive(Clone)]
+pub struct SpatialGridEntry {
+    pub entity: Entity,
+    pub collider: Collider,
+    pub pose: ColliderPose,
+    // The range of cells the collider's bounds reach into
+    min_cell: SpatialCoord,
+    max_cell: SpatialCoord,
 }
 
+const DEFAULT_CELL_SIZE: f32 = 64.;
+
+/*
+Colliders bucketed by every cell their bounds reach into.
+Kept between collision ticks and only updated for colliders that moved or changed
+*/
 #[derive(Resource)]
-pub struct PreviousCollisions {
-    pub collisions: HashSet<(Entity, Entity)>,
+pub struct SpatialGrid {
+    cell_size: f32,
+    cells: HashMap<SpatialCoord, Vec<Entity>>,
+    entries: HashMap<Entity, SpatialGridEntry>,
 }
 
-pub fn collision_tick(
-    mut q_colliders: Query<(Entity, &mut Collider, &Transform)>,
-    mut collision_started_event: EventWriter<CollisionStartEvent>,
-    mut collision_event: EventWriter<IsCollidingEvent>,
-    mut collision_ended_event: EventWriter<CollisionEndEvent>,
-    mut prev_collisions: ResMut<PreviousCollisions>,
-) {
-    let collisions: HashSet<(Entity, Entity)> = HashSet::new();
-    let mut spatial_grid = HashMap::new();
+impl Default for SpatialGrid {
+    fn default() -> Self {
+        SpatialGrid::new(DEFAULT_CELL_SIZE)
+    }
+}
 
-    // Update spatial grid
-    for (entity, mut collider, transform) in q_colliders.iter_mut() {
-        let spatial_coord = vec3_to_spatial_coord(transform.translation);
+impl SpatialGrid {
+    pub fn new(cell_size: f32) -> SpatialGrid {
+        SpatialGrid {
+            cell_size,
+            cells: HashMap::new(),
+            entries: HashMap::new(),
+        }
+    }
 
-        spatial_grid
-            .entry(spatial_coord)
-            .or_insert_with(std::vec::Vec::new);
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
 
-        // Add entity to updated coordinate
-        spatial_grid.get_mut(&spatial_coord).unwrap().push((
-            entity,
-            collider.clone(),
-            transform.translation.truncate(),
-        ));
-        collider.spatial_coord = spatial_coord;
+    // Re-buckets every collider, meant for tuning and not for every frame
+    #[cfg_attr(not(debug_assertions), allow(dead_code))]
+    pub fn set_cell_size(&mut self, cell_size: f32) {
+        let entries: Vec<SpatialGridEntry> = self.entries.drain().map(|(_, entry)| entry).collect();
+
+        self.cell_size = cell_size;
+        self.cells.clear();
+
+        for entry in entries {
+            self.insert(entry.entity, &entry.collider, entry.pose);
+        }
     }
 
-    // Find collisions
-    for (entity, collider, transform) in q_colliders.iter() {
-        let (min_x, min_y) =
-            vec2_to_spatial_coord(collider.min_point(transform.translation.truncate()));
-        let (max_x, max_y) =
-            vec2_to_spatial_coord(collider.max_point(transform.translation.truncate()));
+    pub fn coord(&self, point: Vec2) -> SpatialCoord {
+        let vec = point / self.cell_size;
+        (vec.x.floor() as i32, vec.y.floor() as i32)
+    }
+
+    pub fn cell(&self, coord: SpatialCoord) -> &[Entity] {
+        match self.cells.get(&coord) {
+            Some(entities) => entities,
+            None => &[],
+        }
+    }
+
+    pub fn entry(&self, entity: Entity) -> Option<&SpatialGridEntry> {
+        self.entries.get(&entity)
+    }
 
-        let mut possible_collisions = vec![];
+    // Every collider reaching into the region, sorted and without duplicates
+    pub fn entities_in_region(&self, min: Vec2, max: Vec2) -> Vec<Entity> {
+        let (min_x, min_y) = self.coord(min);
+        let (max_x, max_y) = self.coord(max);
+        let mut entities = vec![];
 
-        // Add all possible collisions
         for x in min_x..max_x + 1 {
             for y in min_y..max_y + 1 {
-                if let Some(entities) = spatial_grid.get(&(x, y)) {
-                    for e in entities {
-                        possible_collisions.push(e);
-                    }
+                entities.extend_from_slice(self.cell((x, y)));
+            }
+        }
+
+        entities.sort_unstable();
+        entities.dedup();
+        entities
+    }
+
+    pub fn occupied_cells(&self) -> impl Iterator<Item = SpatialCoord> + '_ {
+        self.cells.keys().copied()
+    }
+
+    // The smallest and largest cell coordinates that hold a collider
+    pub fn occupied_bounds(&self) -> Option<(SpatialCoord, SpatialCoord)> {
+        self.occupied_cells()
+            .fold(None, |bounds, (x, y)| match bounds {
+                None => Some(((x, y), (x, y))),
+                Some(((min_x, min_y), (max_x, max_y))) => {
+                    Some(((min_x.min(x), min_y.min(y)), (max_x.max(x), max_y.max(y))))
                 }
+            })
+    }
+
+    fn insert(&mut self, entity: Entity, collider: &Collider, pose: ColliderPose) {
+        let min_cell = self.coord(collider.min_point(pose));
+        let max_cell = self.coord(collider.max_point(pose));
+
+        if let Some(entry) = self.entries.get_mut(&entity) {
+            if entry.min_cell == min_cell && entry.max_cell == max_cell {
+                // Still in the same cells, only the entry needs updating
+                entry.collider = collider.clone();
+                entry.pose = pose;
+                return;
             }
+
+            self.remove(entity);
         }
 
-        for (other_entity, other_collider, other_position) in possible_collisions.iter() {
-            // No self collision
-            if *other_entity == entity {
-                continue;
+        for x in min_cell.0..max_cell.0 + 1 {
+            for y in min_cell.1..max_cell.1 + 1 {
+                self.cells.entry((x, y)).or_default().push(entity);
             }
+        }
+
+        self.entries.insert(
+            entity,
+            SpatialGridEntry {
+                entity,
+                collider: collider.clone(),
+                pose,
+                min_cell,
+                max_cell,
+            },
+        );
+    }
+
+    fn remove(&mut self, entity: Entity) {
+        let Some(entry) = self.entries.remove(&entity) else {
+            return;
+        };
+
+        for x in entry.min_cell.0..entry.max_cell.0 + 1 {
+            for y in entry.min_cell.1..entry.max_cell.1 + 1 {
+                let Some(cell) = self.cells.get_mut(&(x, y)) else {
+                    continue;
+                };
 
-            if collider.is_colliding(
-                transform.translation.truncate(),
-                other_collider,
-                *other_position,
-            ) {
-                if collisions.contains(&(*other_entity, entity)) {
-                    continue; // Already logged collision
-                } else {
-                    let collision = Collision {
-                        entity_a: entity,
-                        entity_b: *other_entity,
-                    };
-
-                    let previously_collided = prev_collisions
-                        .collisions
-                        .contains(&(entity, *other_entity))
-                        || prev_collisions
-                            .collisions
-                            .contains(&(*other_entity, entity));
-
-                    if !previously_collided {
-                        collision_started_event.send(CollisionStartEvent { collision })
-                    }
-
-                    collision_event.send(IsCollidingEvent { collision });
+                if let Some(index) = cell.iter().position(|other| *other == entity) {
+                    cell.swap_remove(index);
+                }
+
+                if cell.is_empty() {
+                    self.cells.remove(&(x, y));
                 }
             }
         }
     }
+}
 
-    // Collision Ending
-    for collision in &prev_collisions.collisions {
-        let (a, b) = *collision;
-        let other_collision = &(b, a);
+// Smallest amount of colliders worth handing to another thread
+const NARROW_PHASE_MIN_BATCH: usize = 64;
 
-        if collisions.contains(collision) || collisions.contains(other_collision) {
-            continue;
+pub fn collision_tick(
+    q_changed: Query<(Entity, &Collider, &Transform), Or<(Changed<Collider>, Changed<Transform>)>>,
+    mut q_continuous: Query<(Entity, &mut ContinuousCollision, &Transform)>,
+    mut removed_colliders: RemovedComponents<Collider>,
+    mut collision_started_event: EventWriter<CollisionStartEvent>,
+    mut collision_event: EventWriter<IsCollidingEvent>,
+    mut collision_ended_event: EventWriter<CollisionEndEvent>,
+    mut prev_collisions: ResMut<PreviousCollisions>,
+    mut spatial_grid: ResMut<SpatialGrid>,
+) {
+    let mut collisions: HashSet<CollisionPair> = HashSet::new();
+
+    // Update spatial grid
+    for entity in removed_colliders.iter() {
+        spatial_grid.remove(entity);
+    }
+
+    for (entity, collider, transform) in q_changed.iter() {
+        match collider.layers.is_empty() {
+            true => spatial_grid.remove(entity),
+            false => spatial_grid.insert(entity, collider, transform.into()),
+        }
+    }
+
+    let spatial_grid = &*spatial_grid;
+    let previous_positions: HashMap<Entity, Vec2> = q_continuous
+        .iter()
+        .filter_map(|(entity, continuous, _)| Some((entity, continuous.previous_position?)))
+        .collect();
+
+    // Sorted so the collisions are reported in the same order every run
+    let mut entities: Vec<Entity> = spatial_grid.entries.keys().copied().collect();
+    entities.sort_unstable();
+
+    // Find collisions
+    let batch_size = entities
+        .len()
+        .div_ceil(ComputeTaskPool::get().thread_num())
+        .max(NARROW_PHASE_MIN_BATCH);
+
+    let mut found = narrow_phase(&entities, &previous_positions, spatial_grid, batch_size);
+    // Fast movers report first, so the pairs they share with slower colliders carry their time of impact.
+    // Their hits are sorted by it, so single hit projectiles stop at the earliest contact
+    found.sort_by_key(|(entity, _)| !previous_positions.contains_key(entity));
+
+    for (entity, found) in found {
+        for (other_entity, time_of_impact) in found {
+            let pair = ordered_pair(entity, other_entity);
+
+            if !collisions.insert(pair) {
+                continue; // Already logged collision from the other side
+            }
+
+            let collision = Collision {
+                entity_a: pair.0,
+                entity_b: pair.1,
+                time_of_impact,
+            };
+
+            if !prev_collisions.collisions.contains(&pair) {
+                collision_started_event.send(CollisionStartEvent { collision });
+            }
+
+            collision_event.send(IsCollidingEvent { collision });
         }
+    }
 
+    // Remember where the fast movers are for the next sweep
+    for (_, mut continuous, transform) in q_continuous.iter_mut() {
+        continuous.swept_from = continuous.previous_position;
+        continuous.previous_position = Some(transform.translation.truncate());
+    }
+
+    // Collision Ending
+    // Pairs whose entities were despawned or lost their collider are no longer found above,
+    // so they end here as well and are dropped from the tracked set
+    for (a, b) in prev_collisions.collisions.difference(&collisions) {
         collision_ended_event.send(CollisionEndEvent {
             collision: Collision {
-                entity_a: a,
-                entity_b: b,
+                entity_a: *a,
+                entity_b: *b,
+                time_of_impact: None,
             },
         });
     }
 
     prev_collisions.collisions = collisions;
 }
+
+// Every batch of colliders is tested on its own thread,
+// the batches come back in the order they were spawned in so the result follows `entities`
+fn narrow_phase(
+    entities: &[Entity],
+    previous_positions: &HashMap<Entity, Vec2>,
+    spatial_grid: &SpatialGrid,
+    batch_size: usize,
+) -> Vec<(Entity, Vec<(Entity, Option<f32>)>)> {
+    let batches = ComputeTaskPool::get().scope(|scope| {
+        for batch in entities.chunks(batch_size) {
+            scope.spawn(async move {
+                batch
+                    .iter()
+                    .map(|entity| {
+                        let found = find_collisions(
+                            *entity,
+                            previous_positions.get(entity).copied(),
+                            spatial_grid,
+                        );
+                        (*entity, found)
+                    })
+                    .collect::<Vec<_>>()
+            });
+        }
+    });
+
+    batches.into_iter().flatten().collect()
+}
+
+// Every collider the entity overlaps, plus the ones it swept through if it is a fast mover
+fn find_collisions(
+    entity: Entity,
+    previous_position: Option<Vec2>,
+    spatial_grid: &SpatialGrid,
+) -> Vec<(Entity, Option<f32>)> {
+    let Some(entry) = spatial_grid.entry(entity) else {
+        return vec![];
+    };
+
+    let (collider, pose) = (&entry.collider, entry.pose);
+    let mut found = vec![];
+
+    for other_entity in
+        spatial_grid.entities_in_region(collider.min_point(pose), collider.max_point(pose))
+    {
+        // No self collision
+        if other_entity == entity {
+            continue;
+        }
+
+        let Some(other) = spatial_grid.entry(other_entity) else {
+            continue;
+        };
+
+        if !collider.layers.interacts_with(&other.collider.layers) {
+            continue;
+        }
+
+        if collider.is_colliding(pose, &other.collider, other.pose) {
+            found.push((other.entity, None));
+        }
+    }
+
+    let Some(previous_position) = previous_position else {
+        return found;
+    };
+
+    // Fast movers also collide with everything they passed through since the last tick
+    let swept = find_swept_collisions(
+        entity,
+        collider,
+        previous_position,
+        pose.position,
+        spatial_grid,
+    );
+
+    // What they end up overlapping was reached somewhere along the path as well
+    for (other_entity, time_of_impact) in found.iter_mut() {
+        let swept_time = swept
+            .iter()
+            .find(|(swept_entity, _)| swept_entity == other_entity)
+            .map(|(_, time)| *time);
+        *time_of_impact = Some(swept_time.unwrap_or(1.));
+    }
+
+    for (other_entity, time_of_impact) in swept {
+        if !found.iter().any(|(e, _)| *e == other_entity) {
+            found.push((other_entity, Some(time_of_impact)));
+        }
+    }
+
+    // Earliest contact first
+    found.sort_by(|(entity_a, a), (entity_b, b)| {
+        a.unwrap_or(1.)
+            .total_cmp(&b.unwrap_or(1.))
+            .then(entity_a.cmp(entity_b))
+    });
+    found
+}
+
+// Sweeps the collider, approximated by a circle of its extent, from the previous to the current position
+// and returns every collider it touched on the way with the fraction of the path travelled
+fn find_swept_collisions(
+    entity: Entity,
+    collider: &Collider,
+    from: Vec2,
+    to: Vec2,
+    spatial_grid: &SpatialGrid,
+) -> Vec<(Entity, f32)> {
+    let travelled = to - from;
+    let distance = travelled.length();
+
+    if distance <= f32::EPSILON {
+        return vec![];
+    }
+
+    let direction = travelled / distance;
+    let radius = collider.extent();
+    let mut hits = vec![];
+
+    for other_entity in spatial_grid.entities_in_region(
+        from.min(to) - Vec2::splat(radius),
+        from.max(to) + Vec2::splat(radius),
+    ) {
+        let Some(other) = spatial_grid.entry(other_entity) else {
+            continue;
+        };
+
+        if other.entity == entity || !collider.layers.interacts_with(&other.collider.layers) {
+            continue;
+        }
+
+        if let Some(hit_distance) =
+            cast_against_shape(from, direction, radius, &other.collider.core(other.pose))
+        {
+            if hit_distance <= distance {
+                hits.push((other.entity, hit_distance / distance));
+            }
+        }
+    }
+
+    hits
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use bevy::{ecs::event::ManualEventReader, prelude::*, utils::HashSet};
+
+    use super::*;
+
+    // Only the collision tick, without the game around it
+    pub(crate) fn collision_app() -> App {
+        let mut app = App::new();
+        app.add_plugins(TaskPoolPlugin::default())
+            .add_event::<IsCollidingEvent>()
+            .add_event::<CollisionStartEvent>()
+            .add_event::<CollisionEndEvent>()
+            .insert_resource(PreviousCollisions {
+                collisions: HashSet::new(),
+            })
+            .init_resource::<SpatialGrid>()
+            .add_systems(Update, collision_tick);
+        app
+    }
+
+    pub(crate) fn spawn_circle(app: &mut App, position: Vec2) -> Entity {
+        app.world
+            .spawn((
+                Collider::new_circle(10.),
+                Transform::from_translation(position.extend(0.)),
+            ))
+            .id()
+    }
+
+    // Every event sent since the reader last read, as ordered pairs
+    fn read_pairs<E: Event>(
+        app: &App,
+        reader: &mut ManualEventReader<E>,
+        collision: impl Fn(&E) -> Collision,
+    ) -> Vec<CollisionPair> {
+        reader
+            .iter(app.world.resource::<Events<E>>())
+            .map(|ev| {
+                let collision = collision(ev);
+                (collision.entity_a, collision.entity_b)
+            })
+            .collect()
+    }
+
+    struct Readers {
+        started: ManualEventReader<CollisionStartEvent>,
+        colliding: ManualEventReader<IsCollidingEvent>,
+        ended: ManualEventReader<CollisionEndEvent>,
+    }
+
+    impl Readers {
+        fn new() -> Readers {
+            Readers {
+                started: default(),
+                colliding: default(),
+                ended: default(),
+            }
+        }
+
+        // (started, colliding, ended) pairs of the last update
+        fn read(
+            &mut self,
+            app: &App,
+        ) -> (Vec<CollisionPair>, Vec<CollisionPair>, Vec<CollisionPair>) {
+            (
+                read_pairs(app, &mut self.started, |ev| ev.collision),
+                read_pairs(app, &mut self.colliding, |ev| ev.collision),
+                read_pairs(app, &mut self.ended, |ev| ev.collision),
+            )
+        }
+    }
+
+    #[test]
+    fn collisions_start_stay_and_end() {
+        let mut app = collision_app();
+        let mut readers = Readers::new();
+        let a = spawn_circle(&mut app, Vec2::ZERO);
+        let b = spawn_circle(&mut app, Vec2::new(15., 0.));
+        let pair = ordered_pair(a, b);
+
+        app.update();
+        assert_eq!(readers.read(&app), (vec![pair], vec![pair], vec![]));
+
+        app.update();
+        assert_eq!(readers.read(&app), (vec![], vec![pair], vec![]));
+
+        app.world.get_mut::<Transform>(b).unwrap().translation.x = 100.;
+        app.update();
+        assert_eq!(readers.read(&app), (vec![], vec![], vec![pair]));
+
+        app.update();
+        assert_eq!(readers.read(&app), (vec![], vec![], vec![]));
+    }
+
+    #[test]
+    fn pairs_are_reported_once_with_the_lower_entity_first() {
+        let mut app = collision_app();
+        let mut readers = Readers::new();
+        let a = spawn_circle(&mut app, Vec2::ZERO);
+        let b = spawn_circle(&mut app, Vec2::new(5., 0.));
+
+        assert_eq!(ordered_pair(a, b), ordered_pair(b, a));
+
+        app.update();
+        let (started, colliding, _) = readers.read(&app);
+        assert_eq!(started, vec![(a.min(b), a.max(b))]);
+        assert_eq!(colliding, vec![(a.min(b), a.max(b))]);
+        assert_eq!(
+            app.world.resource::<PreviousCollisions>().collisions,
+            HashSet::from_iter([ordered_pair(b, a)])
+        );
+    }
+
+    #[test]
+    fn despawning_mid_contact_ends_the_collision() {
+        let mut app = collision_app();
+        let mut readers = Readers::new();
+        let a = spawn_circle(&mut app, Vec2::ZERO);
+        let b = spawn_circle(&mut app, Vec2::new(15., 0.));
+        let pair = ordered_pair(a, b);
+
+        app.update();
+        readers.read(&app);
+
+        app.world.despawn(b);
+        app.update();
+        assert_eq!(readers.read(&app), (vec![], vec![], vec![pair]));
+        assert!(app
+            .world
+            .resource::<PreviousCollisions>()
+            .collisions
+            .is_empty());
+        assert!(app.world.resource::<SpatialGrid>().entry(b).is_none());
+
+        // The pair is forgotten and doesn't end a second time
+        app.update();
+        assert_eq!(readers.read(&app), (vec![], vec![], vec![]));
+    }
+
+    #[test]
+    fn fast_movers_report_their_earliest_contact_first() {
+        use crate::collision::layers::CollisionLayer;
+
+        let mut app = collision_app();
+        let mut colliding = ManualEventReader::<IsCollidingEvent>::default();
+
+        // Spawned before the bullet and the first wall, so it would be reported first by entity order
+        let second = app
+            .world
+            .spawn((
+                Collider::new_circle(10.).with_layer(CollisionLayer::Enemy),
+                Transform::from_xyz(200., 0., 0.),
+            ))
+            .id();
+        let first = app
+            .world
+            .spawn((
+                Collider::new_circle(10.).with_layer(CollisionLayer::Enemy),
+                Transform::from_xyz(100., 0., 0.),
+            ))
+            .id();
+        let bullet = app
+            .world
+            .spawn((
+                Collider::new_circle(5.).with_layer(CollisionLayer::PlayerProjectile),
+                ContinuousCollision::default(),
+                Transform::default(),
+            ))
+            .id();
+
+        app.update();
+        colliding.iter(app.world.resource::<Events<IsCollidingEvent>>());
+
+        // Passes through the first collider within a single frame and stops on the second
+        app.world
+            .get_mut::<Transform>(bullet)
+            .unwrap()
+            .translation
+            .x = 200.;
+        app.update();
+
+        let collisions: Vec<Collision> = colliding
+            .iter(app.world.resource::<Events<IsCollidingEvent>>())
+            .map(|ev| ev.collision)
+            .collect();
+        let pairs: Vec<CollisionPair> = collisions
+            .iter()
+            .map(|collision| (collision.entity_a, collision.entity_b))
+            .collect();
+        assert_eq!(
+            pairs,
+            vec![ordered_pair(bullet, first), ordered_pair(bullet, second)]
+        );
+
+        let first_time = collisions[0].time_of_impact.unwrap();
+        let second_time = collisions[1].time_of_impact.unwrap();
+        assert!(first_time < second_time && second_time <= 1.);
+    }
+
+    // The narrow phase done one collider at a time, on this thread
+    fn sequential_narrow_phase(
+        entities: &[Entity],
+        previous_positions: &HashMap<Entity, Vec2>,
+        spatial_grid: &SpatialGrid,
+    ) -> Vec<(Entity, Vec<(Entity, Option<f32>)>)> {
+        entities
+            .iter()
+            .map(|entity| {
+                let previous_position = previous_positions.get(entity).copied();
+                (
+                    *entity,
+                    find_collisions(*entity, previous_position, spatial_grid),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parallel_batches_match_the_sequential_narrow_phase() {
+        use rand::{Rng, SeedableRng};
+        use rand_pcg::Pcg64;
+
+        use crate::collision::layers::CollisionLayer;
+
+        let mut app = collision_app();
+        let mut readers = Readers::new();
+        let mut rng = Pcg64::seed_from_u64(0);
+        let mut bullets = vec![];
+
+        // Crowded enough that most colliders touch a few others
+        for i in 0..400 {
+            let position = Vec3::new(rng.gen_range(-200. ..200.), rng.gen_range(-200. ..200.), 0.);
+            let mut entity = app.world.spawn(Transform::from_translation(position));
+
+            match i % 4 {
+                0 => {
+                    entity.insert((
+                        Collider::new_circle(3.).with_layer(CollisionLayer::PlayerProjectile),
+                        ContinuousCollision::default(),
+                    ));
+                    bullets.push(entity.id());
+                }
+                _ => {
+                    entity.insert(Collider::new_circle(8.).with_layer(CollisionLayer::Enemy));
+                }
+            }
+        }
+
+        app.update();
+
+        let spatial_grid = app.world.resource::<SpatialGrid>();
+        let mut entities: Vec<Entity> = spatial_grid.entries.keys().copied().collect();
+        entities.sort_unstable();
+
+        // The events of the tick are the sequential pairs, deduplicated in order
+        let mut expected = vec![];
+        for (entity, found) in sequential_narrow_phase(&entities, &HashMap::new(), spatial_grid) {
+            for (other_entity, _) in found {
+                let pair = ordered_pair(entity, other_entity);
+                if !expected.contains(&pair) {
+                    expected.push(pair);
+                }
+            }
+        }
+        assert!(expected.len() > 50);
+        assert_eq!(readers.read(&app).1, expected);
+
+        // Fast movers sweep from somewhere else, so the time of impact is compared as well
+        let previous_positions: HashMap<Entity, Vec2> = bullets
+            .iter()
+            .map(|bullet| {
+                let offset = Vec2::new(rng.gen_range(-60. ..60.), rng.gen_range(-60. ..60.));
+                let position = spatial_grid.entry(*bullet).unwrap().pose.position;
+                (*bullet, position + offset)
+            })
+            .collect();
+
+        let sequential = sequential_narrow_phase(&entities, &previous_positions, spatial_grid);
+        for batch_size in [1, 7, NARROW_PHASE_MIN_BATCH, entities.len()] {
+            assert_eq!(
+                narrow_phase(&entities, &previous_positions, spatial_grid, batch_size),
+                sequential,
+                "batch size {batch_size}"
+            );
+        }
+    }
+
+    // Collision stress test, spread over an area much larger than the window
+    const BENCHMARK_ENEMIES: usize = 5000;
+    const BENCHMARK_BULLETS: usize = 2000;
+    const BENCHMARK_HALF_SIZE: f32 = 1500.;
+    const BENCHMARK_FRAMES: u32 = 60;
+
+    #[derive(Component)]
+    struct Drift(Vec2);
+
+    // Run with `cargo test --release collision_benchmark -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn collision_benchmark() {
+        use std::time::{Duration, Instant};
+
+        use rand::{Rng, SeedableRng};
+        use rand_pcg::Pcg64;
+
+        use crate::collision::layers::CollisionLayer;
+
+        for cell_size in [16., 32., 64., 128., 256.] {
+            let mut app = collision_app();
+            app.insert_resource(SpatialGrid::new(cell_size));
+
+            // Seeded so every run is the same scenario
+            let mut rng = Pcg64::seed_from_u64(0);
+            let random_position = |rng: &mut Pcg64| {
+                Vec3::new(
+                    rng.gen_range(-BENCHMARK_HALF_SIZE..BENCHMARK_HALF_SIZE),
+                    rng.gen_range(-BENCHMARK_HALF_SIZE..BENCHMARK_HALF_SIZE),
+                    0.,
+                )
+            };
+
+            for _ in 0..BENCHMARK_ENEMIES {
+                let position = random_position(&mut rng);
+                app.world.spawn((
+                    Collider::new_circle(10.).with_layer(CollisionLayer::Enemy),
+                    Transform::from_translation(position),
+                    Drift(Vec2::from_angle(rng.gen_range(0. ..std::f32::consts::TAU)) * 15.),
+                ));
+            }
+
+            for _ in 0..BENCHMARK_BULLETS {
+                let position = random_position(&mut rng);
+                app.world.spawn((
+                    Collider::new_circle(5.).with_layer(CollisionLayer::PlayerProjectile),
+                    ContinuousCollision::default(),
+                    Transform::from_translation(position),
+                    Drift(Vec2::from_angle(rng.gen_range(0. ..std::f32::consts::TAU)) * 500.),
+                ));
+            }
+
+            // The first tick fills the grid and isn't part of the measurement
+            app.update();
+
+            let mut q_drifting = app.world.query::<(&mut Transform, &Drift)>();
+            let mut total = Duration::ZERO;
+            let mut slowest = Duration::ZERO;
+
+            for _ in 0..BENCHMARK_FRAMES {
+                for (mut transform, drift) in q_drifting.iter_mut(&mut app.world) {
+                    transform.translation += (drift.0 / 60.).extend(0.);
+                }
+
+                let start = Instant::now();
+                app.update();
+                let frame = start.elapsed();
+
+                total += frame;
+                slowest = slowest.max(frame);
+            }
+
+            eprintln!(
+                "cell size {:>4}: {:>7.2}ms average, {:>7.2}ms slowest",
+                cell_size,
+                total.as_secs_f64() * 1000. / BENCHMARK_FRAMES as f64,
+                slowest.as_secs_f64() * 1000.
+            );
+        }
+    }
+}
@@ -0,0 +1,626 @@
+use bevy::prelude::*;
+
+use super::collider::ColliderShape;
+
+/*
+Every collider shape is a convex core (a point, a segment or a polygon) grown by a radius.
+A circle is a point with a radius, a capsule a segment with a radius
+and rects and polygons have no radius at all
+*/
+pub struct ShapeCore {
+    pub vertices: Vec<Vec2>,
+    pub radius: f32,
+}
+
+impl ShapeCore {
+    pub fn new(shape: &ColliderShape, position: Vec2, rotation: f32) -> ShapeCore {
+        let rotation = Vec2::from_angle(rotation);
+        let to_world = |local: Vec2| position + rotation.rotate(local);
+
+        match shape {
+            ColliderShape::Circle(radius) => ShapeCore {
+                vertices: vec![position],
+                radius: *radius,
+            },
+            ColliderShape::Rect(size) => {
+                let half = *size / 2.;
+                ShapeCore {
+                    vertices: vec![
+                        to_world(Vec2::new(-half.x, -half.y)),
+                        to_world(Vec2::new(half.x, -half.y)),
+                        to_world(Vec2::new(half.x, half.y)),
+                        to_world(Vec2::new(-half.x, half.y)),
+                    ],
+                    radius: 0.,
+                }
+            }
+            ColliderShape::Capsule {
+                half_length,
+                radius,
+            } => ShapeCore {
+                vertices: vec![
+                    to_world(Vec2::new(-half_length, 0.)),
+                    to_world(Vec2::new(*half_length, 0.)),
+                ],
+                radius: *radius,
+            },
+            ColliderShape::Polygon(vertices) => ShapeCore {
+                vertices: vertices.iter().map(|vertex| to_world(*vertex)).collect(),
+                radius: 0.,
+            },
+        }
+    }
+
+    pub fn min_point(&self) -> Vec2 {
+        self.vertices
+            .iter()
+            .fold(Vec2::splat(f32::MAX), |min, v| min.min(*v))
+            - self.radius
+    }
+
+    pub fn max_point(&self) -> Vec2 {
+        self.vertices
+            .iter()
+            .fold(Vec2::splat(f32::MIN), |max, v| max.max(*v))
+            + self.radius
+    }
+
+    pub fn edges(&self) -> Vec<(Vec2, Vec2)> {
+        match self.vertices.len() {
+            0 | 1 => vec![],
+            2 => vec![(self.vertices[0], self.vertices[1])],
+            count => (0..count)
+                .map(|i| (self.vertices[i], self.vertices[(i + 1) % count]))
+                .collect(),
+        }
+    }
+
+    fn is_polygon(&self) -> bool {
+        self.vertices.len() >= 3
+    }
+
+    fn center(&self) -> Vec2 {
+        self.vertices.iter().sum::<Vec2>() / self.vertices.len().max(1) as f32
+    }
+
+    // Normalized edge normals to test separation along
+    fn axes(&self) -> Vec<Vec2> {
+        self.edges()
+            .iter()
+            .filter_map(|(start, end)| (*end - *start).perp().try_normalize())
+            .collect()
+    }
+}
+
+// Whether two shapes overlap, touching counts as overlapping
+pub fn cores_overlap(a: &ShapeCore, b: &ShapeCore) -> bool {
+    if a.is_polygon() && b.is_polygon() && a.radius == 0. && b.radius == 0. {
+        return polygons_overlap_sat(&a.vertices, &b.vertices);
+    }
+
+    core_distance(a, b) <= a.radius + b.radius
+}
+
+// Separating axis test between two convex polygons
+fn polygons_overlap_sat(a: &[Vec2], b: &[Vec2]) -> bool {
+    for polygon in [a, b] {
+        for i in 0..polygon.len() {
+            let edge = polygon[(i + 1) % polygon.len()] - polygon[i];
+            let axis = edge.perp();
+
+            let (min_a, max_a) = project(a, axis);
+            let (min_b, max_b) = project(b, axis);
+
+            if max_a < min_b || max_b < min_a {
+                return false; // Found a separating axis
+            }
+        }
+    }
+
+    true
+}
+
+fn project(vertices: &[Vec2], axis: Vec2) -> (f32, f32) {
+    vertices
+        .iter()
+        .map(|vertex| vertex.dot(axis))
+        .fold((f32::MAX, f32::MIN), |(min, max), projection| {
+            (min.min(projection), max.max(projection))
+        })
+}
+
+// Distance between the cores without their radius, zero if they intersect
+pub fn core_distance(a: &ShapeCore, b: &ShapeCore) -> f32 {
+    if cores_intersect(a, b) {
+        return 0.;
+    }
+
+    let (on_a, on_b) = closest_points(a, b);
+    on_a.distance(on_b)
+}
+
+fn cores_intersect(a: &ShapeCore, b: &ShapeCore) -> bool {
+    if (a.is_polygon() && b.vertices.iter().any(|v| polygon_contains(&a.vertices, *v)))
+        || (b.is_polygon() && a.vertices.iter().any(|v| polygon_contains(&b.vertices, *v)))
+    {
+        return true;
+    }
+
+    let b_edges = b.edges();
+
+    a.edges().iter().any(|(a_start, a_end)| {
+        b_edges
+            .iter()
+            .any(|(b_start, b_end)| segments_intersect(*a_start, *a_end, *b_start, *b_end))
+    })
+}
+
+// The closest points of two cores that don't intersect, the first one on a and the second on b
+fn closest_points(a: &ShapeCore, b: &ShapeCore) -> (Vec2, Vec2) {
+    // Without an intersection the closest points involve at least one vertex
+    let from_a = a
+        .vertices
+        .iter()
+        .map(|vertex| (*vertex, closest_point_on_core(*vertex, b)));
+    let from_b = b
+        .vertices
+        .iter()
+        .map(|vertex| (closest_point_on_core(*vertex, a), *vertex));
+
+    from_a
+        .chain(from_b)
+        .min_by(|(a1, b1), (a2, b2)| a1.distance(*b1).total_cmp(&a2.distance(*b2)))
+        .unwrap_or((Vec2::ZERO, Vec2::ZERO))
+}
+
+fn closest_point_on_core(point: Vec2, core: &ShapeCore) -> Vec2 {
+    let edges = core.edges();
+
+    if edges.is_empty() {
+        return core.vertices.first().copied().unwrap_or(point);
+    }
+
+    edges
+        .iter()
+        .map(|(start, end)| closest_point_on_segment(point, *start, *end))
+        .min_by(|a, b| a.distance(point).total_cmp(&b.distance(point)))
+        .unwrap_or(point)
+}
+
+/*
+The smallest translation that moves a out of b, None if they don't overlap
+*/
+pub fn penetration(a: &ShapeCore, b: &ShapeCore) -> Option<Vec2> {
+    let radius = a.radius + b.radius;
+
+    if !cores_intersect(a, b) {
+        let (on_a, on_b) = closest_points(a, b);
+        let distance = on_a.distance(on_b);
+
+        if distance >= radius {
+            return None;
+        }
+
+        let normal = match (on_a - on_b).try_normalize() {
+            Some(normal) => normal,
+            None => fallback_normal(a, b),
+        };
+        return Some(normal * (radius - distance));
+    }
+
+    // The cores intersect, push along the axis with the smallest overlap
+    let axes: Vec<Vec2> = a.axes().into_iter().chain(b.axes()).collect();
+    if axes.is_empty() {
+        return Some(fallback_normal(a, b) * radius);
+    }
+
+    let mut smallest: Option<Vec2> = None;
+
+    for axis in axes {
+        let (min_a, max_a) = project(&a.vertices, axis);
+        let (min_b, max_b) = project(&b.vertices, axis);
+        let push_forward = max_b - min_a + radius;
+        let push_backward = max_a - min_b + radius;
+
+        let push = match push_forward < push_backward {
+            true => axis * push_forward,
+            false => -axis * push_backward,
+        };
+
+        let is_smaller = match smallest {
+            Some(smallest) => push.length() < smallest.length(),
+            None => true,
+        };
+
+        if is_smaller {
+            smallest = Some(push);
+        }
+    }
+
+    smallest
+}
+
+/*
+Points out of b towards a where the two are closest, or along the push that moves a out of b if they overlap.
+Also works for shapes that only touch, unlike `penetration`
+*/
+pub fn contact_normal(a: &ShapeCore, b: &ShapeCore) -> Vec2 {
+    if let Some(normal) = penetration(a, b).and_then(|push| push.try_normalize()) {
+        return normal;
+    }
+
+    if cores_intersect(a, b) {
+        return fallback_normal(a, b);
+    }
+
+    let (on_a, on_b) = closest_points(a, b);
+    (on_a - on_b)
+        .try_normalize()
+        .unwrap_or_else(|| fallback_normal(a, b))
+}
+
+// Pushes a away from b's center, or to the right if they share the same center
+fn fallback_normal(a: &ShapeCore, b: &ShapeCore) -> Vec2 {
+    (a.center() - b.center()).try_normalize().unwrap_or(Vec2::X)
+}
+
+// Works for either winding order
+pub fn polygon_contains(vertices: &[Vec2], point: Vec2) -> bool {
+    let mut sign = 0.;
+
+    for i in 0..vertices.len() {
+        let edge = vertices[(i + 1) % vertices.len()] - vertices[i];
+        let cross = edge.perp_dot(point - vertices[i]);
+
+        if cross == 0. {
+            continue; // On the edge's line
+        }
+
+        if sign == 0. {
+            sign = cross.signum();
+        } else if cross.signum() != sign {
+            return false;
+        }
+    }
+
+    true
+}
+
+fn segments_intersect(a_start: Vec2, a_end: Vec2, b_start: Vec2, b_end: Vec2) -> bool {
+    let a = a_end - a_start;
+    let b = b_end - b_start;
+    let denominator = a.perp_dot(b);
+
+    if denominator == 0. {
+        // Parallel, only intersect if collinear and overlapping
+        return distance_to_segment(a_start, b_start, b_end) == 0.
+            || distance_to_segment(a_end, b_start, b_end) == 0.
+            || distance_to_segment(b_start, a_start, a_end) == 0.;
+    }
+
+    let t = (b_start - a_start).perp_dot(b) / denominator;
+    let u = (b_start - a_start).perp_dot(a) / denominator;
+
+    (0. ..=1.).contains(&t) && (0. ..=1.).contains(&u)
+}
+
+pub fn distance_to_segment(point: Vec2, start: Vec2, end: Vec2) -> f32 {
+    point.distance(closest_point_on_segment(point, start, end))
+}
+
+fn closest_point_on_segment(point: Vec2, start: Vec2, end: Vec2) -> Vec2 {
+    let segment = end - start;
+    let length_squared = segment.length_squared();
+
+    if length_squared == 0. {
+        return start;
+    }
+
+    let t = ((point - start).dot(segment) / length_squared).clamp(0., 1.);
+    start + segment * t
+}
+
+// Distance from the point to the shape's edge, zero if the point is inside
+pub fn distance_to_shape(point: Vec2, core: &ShapeCore) -> f32 {
+    let point_core = ShapeCore {
+        vertices: vec![point],
+        radius: 0.,
+    };
+
+    (core_distance(&point_core, core) - core.radius).max(0.)
+}
+
+// Distance along the ray until a circle of `radius` moving from origin touches the shape
+pub fn cast_against_shape(
+    origin: Vec2,
+    direction: Vec2,
+    radius: f32,
+    core: &ShapeCore,
+) -> Option<f32> {
+    let total_radius = core.radius + radius;
+
+    if distance_to_shape(origin, core) <= radius {
+        return Some(0.);
+    }
+
+    // The grown shape's boundary is made out of circles around the vertices
+    // and the edges pushed outwards by the radius
+    let mut closest: Option<f32> = None;
+    let mut consider = |distance: Option<f32>| {
+        if let Some(distance) = distance {
+            closest = Some(closest.map_or(distance, |closest| closest.min(distance)));
+        }
+    };
+
+    for vertex in core.vertices.iter() {
+        consider(ray_circle(origin, direction, *vertex, total_radius));
+    }
+
+    for (start, end) in core.edges() {
+        let offset = (end - start).perp().normalize_or_zero() * total_radius;
+
+        consider(ray_segment(origin, direction, start + offset, end + offset));
+        consider(ray_segment(origin, direction, start - offset, end - offset));
+    }
+
+    closest
+}
+
+// Expects a normalized direction, returns 0 if the origin is already inside
+fn ray_circle(origin: Vec2, direction: Vec2, center: Vec2, radius: f32) -> Option<f32> {
+    let to_origin = origin - center;
+    let c = to_origin.length_squared() - radius * radius;
+
+    if c <= 0. {
+        return Some(0.);
+    }
+
+    let b = to_origin.dot(direction);
+    if b > 0. {
+        return None; // Moving away from the circle
+    }
+
+    let discriminant = b * b - c;
+    if discriminant < 0. {
+        return None;
+    }
+
+    Some(-b - discriminant.sqrt())
+}
+
+fn ray_segment(origin: Vec2, direction: Vec2, start: Vec2, end: Vec2) -> Option<f32> {
+    let segment = end - start;
+    let denominator = direction.perp_dot(segment);
+
+    if denominator == 0. {
+        return None; // Parallel rays can only touch the segment at its end circles
+    }
+
+    let t = (start - origin).perp_dot(segment) / denominator;
+    let u = (start - origin).perp_dot(direction) / denominator;
+
+    if t >= 0. && (0. ..=1.).contains(&u) {
+        Some(t)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::TAU;
+
+    use rand::{Rng, SeedableRng};
+    use rand_pcg::Pcg64;
+
+    use super::*;
+    use crate::collision::collider::{Collider, ColliderPose};
+
+    const CASES: usize = 150;
+    // Spacing of the reference's sample points
+    const STEP: f32 = 0.5;
+
+    type ShapeMaker = fn(&mut Pcg64) -> (Collider, ColliderPose);
+
+    fn pose(rng: &mut Pcg64, rotation: f32) -> ColliderPose {
+        ColliderPose {
+            position: Vec2::new(rng.gen_range(-20.0..20.0), rng.gen_range(-20.0..20.0)),
+            rotation,
+        }
+    }
+
+    fn rect(rng: &mut Pcg64) -> (Collider, ColliderPose) {
+        let size = Vec2::new(rng.gen_range(4.0..40.0), rng.gen_range(4.0..40.0));
+        // Half of them axis aligned so the fast paths get tested as well
+        let rotation = match rng.gen_bool(0.5) {
+            true => 0.,
+            false => rng.gen_range(0.0..TAU),
+        };
+        (Collider::new_rect(size), pose(rng, rotation))
+    }
+
+    fn circle(rng: &mut Pcg64) -> (Collider, ColliderPose) {
+        (
+            Collider::new_circle(rng.gen_range(2.0..20.0)),
+            pose(rng, 0.),
+        )
+    }
+
+    fn capsule(rng: &mut Pcg64) -> (Collider, ColliderPose) {
+        let collider = Collider::new_capsule(rng.gen_range(2.0..20.0), rng.gen_range(1.0..10.0));
+        let rotation = rng.gen_range(0.0..TAU);
+        (collider, pose(rng, rotation))
+    }
+
+    // Counter clockwise points on a circle, which always form a convex polygon
+    fn polygon(rng: &mut Pcg64) -> (Collider, ColliderPose) {
+        let radius = rng.gen_range(5.0..20.0);
+        let mut angles: Vec<f32> = (0..rng.gen_range(3..8))
+            .map(|_| rng.gen_range(0.0..TAU))
+            .collect();
+        angles.sort_by(f32::total_cmp);
+
+        let vertices = angles
+            .iter()
+            .map(|angle| Vec2::from_angle(*angle) * radius)
+            .collect();
+        let rotation = rng.gen_range(0.0..TAU);
+        (Collider::new_polygon(vertices), pose(rng, rotation))
+    }
+
+    /*
+    How far outside the shape the point is, zero or less inside.
+    Rects and polygons use the largest distance to an edge's line,
+    which is exact inside and never more than the real distance outside
+    */
+    fn reference_distance(point: Vec2, collider: &Collider, pose: ColliderPose) -> f32 {
+        let local = Vec2::from_angle(-pose.rotation).rotate(point - pose.position);
+
+        match collider.shape() {
+            ColliderShape::Circle(radius) => local.length() - radius,
+            ColliderShape::Rect(size) => {
+                let outside = local.abs() - *size / 2.;
+                outside.x.max(outside.y)
+            }
+            ColliderShape::Capsule {
+                half_length,
+                radius,
+            } => local.distance(Vec2::new(local.x.clamp(-half_length, *half_length), 0.)) - radius,
+            ColliderShape::Polygon(vertices) => (0..vertices.len())
+                .map(|i| {
+                    let (start, end) = (vertices[i], vertices[(i + 1) % vertices.len()]);
+                    // Counter clockwise, so the outside is to the right of every edge
+                    let outwards = -(end - start).perp().normalize();
+                    (local - start).dot(outwards)
+                })
+                .fold(f32::MIN, f32::max),
+        }
+    }
+
+    #[derive(PartialEq, Debug)]
+    enum Reference {
+        Overlapping,
+        Separate,
+        // Closer than the sampling can tell apart
+        Touching,
+    }
+
+    // Looks for a sample point inside both shapes
+    fn reference_overlap(
+        (a, pose_a): (&Collider, ColliderPose),
+        (b, pose_b): (&Collider, ColliderPose),
+    ) -> Reference {
+        let min = (pose_a.position - a.extent()).max(pose_b.position - b.extent()) - STEP;
+        let max = (pose_a.position + a.extent()).min(pose_b.position + b.extent()) + STEP;
+        let mut is_touching = false;
+
+        let mut x = min.x;
+        while x <= max.x {
+            let mut y = min.y;
+            while y <= max.y {
+                let point = Vec2::new(x, y);
+                let distance_a = reference_distance(point, a, pose_a);
+                let distance_b = reference_distance(point, b, pose_b);
+
+                if distance_a <= 0. && distance_b <= 0. {
+                    return Reference::Overlapping;
+                }
+
+                // Any overlap is at most half a diagonal away from a sample point
+                if distance_a <= STEP && distance_b <= STEP {
+                    is_touching = true;
+                }
+
+                y += STEP;
+            }
+            x += STEP;
+        }
+
+        match is_touching {
+            true => Reference::Touching,
+            false => Reference::Separate,
+        }
+    }
+
+    fn check_against_reference(seed: u64, make_a: ShapeMaker, make_b: ShapeMaker) {
+        let mut rng = Pcg64::seed_from_u64(seed);
+        let mut checked = 0;
+
+        for case in 0..CASES {
+            let (a, pose_a) = make_a(&mut rng);
+            let (b, pose_b) = make_b(&mut rng);
+            let (core_a, core_b) = (a.core(pose_a), b.core(pose_b));
+            let expected = reference_overlap((&a, pose_a), (&b, pose_b));
+            let push = penetration(&core_a, &core_b);
+
+            match expected {
+                Reference::Touching => continue,
+                Reference::Separate => {
+                    assert!(!a.is_colliding(pose_a, &b, pose_b), "case {case}");
+                    assert!(!cores_overlap(&core_a, &core_b), "case {case}");
+                    assert_eq!(push, None, "case {case}");
+                }
+                Reference::Overlapping => {
+                    assert!(a.is_colliding(pose_a, &b, pose_b), "case {case}");
+                    assert!(cores_overlap(&core_a, &core_b), "case {case}");
+
+                    // Pushing a out by the penetration separates the shapes
+                    let push = push.unwrap_or_else(|| panic!("case {case} has no penetration"));
+                    let moved = ColliderPose {
+                        position: pose_a.position + push + push.normalize_or_zero() * 2. * STEP,
+                        ..pose_a
+                    };
+                    assert_ne!(
+                        reference_overlap((&a, moved), (&b, pose_b)),
+                        Reference::Overlapping,
+                        "case {case} is still overlapping after the push"
+                    );
+
+                    // For shapes without a radius it's also the smallest push along its direction
+                    if core_a.radius == 0. && core_b.radius == 0. {
+                        let halfway = ColliderPose {
+                            position: pose_a.position + push / 2.,
+                            ..pose_a
+                        };
+                        assert_ne!(
+                            reference_overlap((&a, halfway), (&b, pose_b)),
+                            Reference::Separate,
+                            "case {case} separates with half the push"
+                        );
+                    }
+                }
+            }
+
+            checked += 1;
+        }
+
+        // Most cases have to be decided, or the test doesn't test much
+        assert!(checked > CASES / 2, "only {checked} cases checked");
+    }
+
+    #[test]
+    fn rect_rect_matches_reference() {
+        check_against_reference(1, rect, rect);
+    }
+
+    #[test]
+    fn rect_circle_matches_reference() {
+        check_against_reference(2, rect, circle);
+        check_against_reference(3, circle, rect);
+    }
+
+    #[test]
+    fn capsules_match_reference() {
+        check_against_reference(4, capsule, capsule);
+        check_against_reference(5, capsule, circle);
+        check_against_reference(6, rect, capsule);
+        check_against_reference(7, capsule, polygon);
+    }
+
+    #[test]
+    fn polygons_match_reference() {
+        check_against_reference(8, polygon, polygon);
+        check_against_reference(9, polygon, rect);
+        check_against_reference(10, circle, polygon);
+    }
+}
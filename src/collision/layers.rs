@@ -0,0 +1,102 @@
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CollisionLayer {
+    Player,
+    Enemy,
+    PlayerProjectile,
+    EnemyProjectile,
+    Pickup,
+    UI,
+    Obstacle,
+}
+
+impl CollisionLayer {
+    fn bit(&self) -> u32 {
+        1 << *self as u32
+    }
+
+    // The layers this layer wants to receive collisions with by default
+    pub fn default_mask(&self) -> Vec<CollisionLayer> {
+        match self {
+            CollisionLayer::Player => vec![
+                CollisionLayer::Enemy,
+                CollisionLayer::EnemyProjectile,
+                CollisionLayer::Pickup,
+                CollisionLayer::Obstacle,
+            ],
+            CollisionLayer::Enemy => vec![
+                CollisionLayer::Player,
+                CollisionLayer::Enemy,
+                CollisionLayer::PlayerProjectile,
+                CollisionLayer::Obstacle,
+            ],
+            CollisionLayer::PlayerProjectile => {
+                vec![CollisionLayer::Enemy, CollisionLayer::Obstacle]
+            }
+            CollisionLayer::EnemyProjectile => {
+                vec![CollisionLayer::Player, CollisionLayer::Obstacle]
+            }
+            CollisionLayer::Pickup => vec![CollisionLayer::Player],
+            // Selection elements check the cursor against their own collider,
+            // the empty mask keeps them out of the spatial grid and its queries
+            CollisionLayer::UI => vec![],
+            CollisionLayer::Obstacle => vec![
+                CollisionLayer::Player,
+                CollisionLayer::Enemy,
+                CollisionLayer::PlayerProjectile,
+                CollisionLayer::EnemyProjectile,
+            ],
+        }
+    }
+}
+
+/*
+Which layers a collider is part of and which layers it can collide with
+*/
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CollisionLayers {
+    memberships: u32,
+    mask: u32,
+}
+
+impl CollisionLayers {
+    // Part of every layer and collides with everything
+    pub const ALL: CollisionLayers = CollisionLayers {
+        memberships: u32::MAX,
+        mask: u32::MAX,
+    };
+
+    pub fn new(memberships: &[CollisionLayer], mask: &[CollisionLayer]) -> CollisionLayers {
+        CollisionLayers {
+            memberships: to_bits(memberships),
+            mask: to_bits(mask),
+        }
+    }
+
+    pub fn from_layer(layer: CollisionLayer) -> CollisionLayers {
+        CollisionLayers::new(&[layer], &layer.default_mask())
+    }
+
+    pub fn contains(&self, layer: CollisionLayer) -> bool {
+        self.memberships & layer.bit() != 0
+    }
+
+    // Colliders without a mask stay out of the spatial grid, so pairs and spatial queries skip them
+    pub fn is_empty(&self) -> bool {
+        self.mask == 0
+    }
+
+    // Both sides have to accept each other for a pair to be tested
+    pub fn interacts_with(&self, other: &CollisionLayers) -> bool {
+        self.memberships & other.mask != 0 && other.memberships & self.mask != 0
+    }
+}
+
+impl Default for CollisionLayers {
+    fn default() -> Self {
+        CollisionLayers::ALL
+    }
+}
+
+fn to_bits(layers: &[CollisionLayer]) -> u32 {
+    layers.iter().fold(0, |bits, layer| bits | layer.bit())
+}
@@ -0,0 +1,70 @@
+use bevy::prelude::*;
+
+use super::{
+    collider::{Collider, ColliderPose, IsCollidingEvent},
+    geometry::penetration,
+};
+
+/*
+Solid bodies can't overlap each other, overlapping bodies get pushed apart.
+The heavier a body is, the less it gets pushed, by other bodies and by knockback
+*/
+#[derive(Component, Clone, Copy)]
+pub struct Solid {
+    pub mass: f32,
+}
+
+impl Solid {
+    // Never moved by other bodies
+    pub const STATIC: Solid = Solid {
+        mass: f32::INFINITY,
+    };
+
+    pub fn new(mass: f32) -> Solid {
+        Solid { mass }
+    }
+
+    pub fn is_static(&self) -> bool {
+        self.mass.is_infinite()
+    }
+}
+
+pub fn resolve_overlaps(
+    mut q_solids: Query<(&Solid, &Collider, &mut Transform)>,
+    mut collisions: EventReader<IsCollidingEvent>,
+) {
+    for collision_ev in collisions.iter() {
+        let Ok([(solid_a, collider_a, mut transform_a), (solid_b, collider_b, mut transform_b)]) =
+            q_solids.get_many_mut([
+                collision_ev.collision.entity_a,
+                collision_ev.collision.entity_b,
+            ])
+        else {
+            continue;
+        };
+
+        if solid_a.is_static() && solid_b.is_static() {
+            continue;
+        }
+
+        // Uses the current transforms so earlier pushes this frame are taken into account
+        let Some(push) = penetration(
+            &collider_a.core(ColliderPose::from(&*transform_a)),
+            &collider_b.core(ColliderPose::from(&*transform_b)),
+        ) else {
+            continue;
+        };
+
+        let (share_a, share_b) = match (solid_a.is_static(), solid_b.is_static()) {
+            (true, _) => (0., 1.),
+            (_, true) => (1., 0.),
+            _ => {
+                let total_mass = solid_a.mass + solid_b.mass;
+                (solid_b.mass / total_mass, solid_a.mass / total_mass)
+            }
+        };
+
+        transform_a.translation += (push * share_a).extend(0.);
+        transform_b.translation -= (push * share_b).extend(0.);
+    }
+}
@@ -0,0 +1,338 @@
+use bevy::{ecs::system::SystemParam, prelude::*, utils::HashSet};
+
+use crate::combat::{
+    health::Dead,
+    teams::{Team, TeamMember},
+};
+
+use super::{
+    collider::{SpatialGrid, SpatialGridEntry},
+    geometry::{cast_against_shape, distance_to_segment, distance_to_shape},
+    layers::CollisionLayer,
+};
+
+/*
+Restricts which colliders a spatial query can return
+*/
+#[derive(Default, Clone)]
+pub struct SpatialQueryFilter {
+    pub team: Option<Team>,
+    pub layers: Vec<CollisionLayer>,
+    pub excluded: Vec<Entity>,
+}
+
+impl SpatialQueryFilter {
+    pub fn with_team(mut self, team: Team) -> SpatialQueryFilter {
+        self.team = Some(team);
+        self
+    }
+
+    // Only colliders that are part of at least one of the layers are returned
+    pub fn with_layers(mut self, layers: &[CollisionLayer]) -> SpatialQueryFilter {
+        self.layers = layers.to_vec();
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CastHit {
+    pub entity: Entity,
+    // Distance travelled along the cast direction until the first contact
+    pub distance: f32,
+}
+
+/*
+Queries against the spatial grid of the last collision tick, dead entities are never returned
+*/
+#[derive(SystemParam)]
+pub struct SpatialQuery<'w, 's> {
+    grid: Res<'w, SpatialGrid>,
+    q_teams: Query<'w, 's, &'static TeamMember>,
+    // Corpses keep their collider in the grid until the next collision tick
+    q_dead: Query<'w, 's, (), With<Dead>>,
+}
+
+impl<'w, 's> SpatialQuery<'w, 's> {
+    // All entities whose collider overlaps the circle
+    pub fn circle_query(
+        &self,
+        center: Vec2,
+        radius: f32,
+        filter: &SpatialQueryFilter,
+    ) -> Vec<Entity> {
+        let reach = Vec2::splat(radius);
+        let mut entities = vec![];
+
+        self.for_each_in_region(center - reach, center + reach, |entry| {
+            if self.matches(entry, filter)
+                && distance_to_shape(center, &entry.collider.core(entry.pose)) <= radius
+            {
+                entities.push(entry.entity);
+            }
+        });
+
+        entities
+    }
+
+    pub fn ray_cast(
+        &self,
+        origin: Vec2,
+        direction: Vec2,
+        max_distance: f32,
+        filter: &SpatialQueryFilter,
+    ) -> Option<CastHit> {
+        self.circle_cast(origin, 0., direction, max_distance, filter)
+    }
+
+    // Sweeps a circle along the direction and returns the first collider it touches
+    pub fn circle_cast(
+        &self,
+        origin: Vec2,
+        radius: f32,
+        direction: Vec2,
+        max_distance: f32,
+        filter: &SpatialQueryFilter,
+    ) -> Option<CastHit> {
+        let direction = direction.normalize_or_zero();
+        if direction == Vec2::ZERO {
+            return None;
+        }
+
+        let end = origin + direction * max_distance;
+        let mut closest: Option<CastHit> = None;
+
+        self.for_each_in_region(
+            origin.min(end) - Vec2::splat(radius),
+            origin.max(end) + Vec2::splat(radius),
+            |entry| {
+                if !self.matches(entry, filter) {
+                    return;
+                }
+
+                // Cheap rejection of colliders far away from the swept segment
+                if distance_to_segment(entry.pose.position, origin, end)
+                    > radius + entry.collider.extent()
+                {
+                    return;
+                }
+
+                let Some(distance) =
+                    cast_against_shape(origin, direction, radius, &entry.collider.core(entry.pose))
+                else {
+                    return;
+                };
+
+                let is_closer = match closest {
+                    None => true,
+                    Some(hit) => {
+                        distance < hit.distance
+                            || (distance == hit.distance && entry.entity < hit.entity)
+                    }
+                };
+
+                if distance <= max_distance && is_closer {
+                    closest = Some(CastHit {
+                        entity: entry.entity,
+                        distance,
+                    });
+                }
+            },
+        );
+
+        closest
+    }
+
+    // Up to `count` entities sorted by the distance between their center and the point
+    pub fn nearest(
+        &self,
+        point: Vec2,
+        count: usize,
+        max_distance: f32,
+        filter: &SpatialQueryFilter,
+    ) -> Vec<(Entity, f32)> {
+        let mut found: Vec<(Entity, f32)> = vec![];
+
+        if count == 0 {
+            return found;
+        }
+
+        let Some(((min_x, min_y), (max_x, max_y))) = self.grid.occupied_bounds() else {
+            return found;
+        };
+
+        let cell_size = self.grid.cell_size();
+        let (center_x, center_y) = self.grid.coord(point);
+        // Rings past this one lie completely outside the occupied cells
+        let last_ring = (center_x - min_x)
+            .max(max_x - center_x)
+            .max(center_y - min_y)
+            .max(max_y - center_y)
+            .max(0);
+        // Large colliders are in several cells and must only be counted once
+        let mut visited: HashSet<Entity> = HashSet::new();
+        let mut ring = 0;
+
+        loop {
+            for (x, y) in ring_coords(center_x, center_y, ring) {
+                for entity in self.grid.cell((x, y)) {
+                    if !visited.insert(*entity) {
+                        continue;
+                    }
+
+                    let Some(entry) = self.grid.entry(*entity) else {
+                        continue;
+                    };
+
+                    if !self.matches(entry, filter) {
+                        continue;
+                    }
+
+                    let distance = entry.pose.position.distance(point);
+                    if distance <= max_distance {
+                        found.push((entry.entity, distance));
+                    }
+                }
+            }
+
+            // Every center closer than this has been visited after this ring
+            let searched_distance = ring as f32 * cell_size;
+            let enough_found = found
+                .iter()
+                .filter(|(_, distance)| *distance <= searched_distance)
+                .count()
+                >= count;
+
+            if enough_found || searched_distance > max_distance || ring >= last_ring {
+                break;
+            }
+
+            ring += 1;
+        }
+
+        found.sort_by(|(entity_a, a), (entity_b, b)| a.total_cmp(b).then(entity_a.cmp(entity_b)));
+        found.truncate(count);
+        found
+    }
+
+    fn matches(&self, entry: &SpatialGridEntry, filter: &SpatialQueryFilter) -> bool {
+        if filter.excluded.contains(&entry.entity) || self.q_dead.contains(entry.entity) {
+            return false;
+        }
+
+        if !filter.layers.is_empty()
+            && !filter
+                .layers
+                .iter()
+                .any(|layer| entry.collider.layers().contains(*layer))
+        {
+            return false;
+        }
+
+        match filter.team {
+            Some(team) => match self.q_teams.get(entry.entity) {
+                Ok(member) => member.team == team,
+                Err(_) => false,
+            },
+            None => true,
+        }
+    }
+
+    fn for_each_in_region(&self, min: Vec2, max: Vec2, mut f: impl FnMut(&SpatialGridEntry)) {
+        for entity in self.grid.entities_in_region(min, max) {
+            if let Some(entry) = self.grid.entry(entity) {
+                f(entry);
+            }
+        }
+    }
+}
+
+// The cells at exactly `ring` cells distance around the center cell
+fn ring_coords(center_x: i32, center_y: i32, ring: i32) -> Vec<(i32, i32)> {
+    if ring == 0 {
+        return vec![(center_x, center_y)];
+    }
+
+    let mut coords = vec![];
+    for x in center_x - ring..center_x + ring + 1 {
+        coords.push((x, center_y - ring));
+        coords.push((x, center_y + ring));
+    }
+    for y in center_y - ring + 1..center_y + ring {
+        coords.push((center_x - ring, y));
+        coords.push((center_x + ring, y));
+    }
+
+    coords
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{ecs::system::SystemState, prelude::*};
+
+    use super::*;
+    use crate::collision::collider::tests::{collision_app, spawn_circle};
+
+    #[test]
+    fn nearest_without_a_max_distance_stops_at_the_occupied_cells() {
+        let mut app = collision_app();
+        let near = spawn_circle(&mut app, Vec2::new(20., 0.));
+        let far = spawn_circle(&mut app, Vec2::new(-500., 300.));
+        app.update();
+
+        let mut state: SystemState<SpatialQuery> = SystemState::new(&mut app.world);
+        let spatial_query = state.get(&app.world);
+
+        // Asks for more than there are, so only running out of cells ends the search
+        let found = spatial_query.nearest(
+            Vec2::new(2000., 0.),
+            5,
+            f32::INFINITY,
+            &SpatialQueryFilter::default(),
+        );
+        assert_eq!(
+            found.iter().map(|(entity, _)| *entity).collect::<Vec<_>>(),
+            vec![near, far]
+        );
+    }
+
+    #[test]
+    fn nearest_in_an_empty_grid_finds_nothing() {
+        let mut app = collision_app();
+        app.update();
+
+        let mut state: SystemState<SpatialQuery> = SystemState::new(&mut app.world);
+        let spatial_query = state.get(&app.world);
+
+        assert!(spatial_query
+            .nearest(Vec2::ZERO, 1, f32::INFINITY, &SpatialQueryFilter::default())
+            .is_empty());
+    }
+
+    #[test]
+    fn dead_entities_are_skipped() {
+        let mut app = collision_app();
+        let dead = spawn_circle(&mut app, Vec2::new(20., 0.));
+        let alive = spawn_circle(&mut app, Vec2::new(60., 0.));
+        app.update();
+        app.world.entity_mut(dead).insert(Dead);
+
+        let mut state: SystemState<SpatialQuery> = SystemState::new(&mut app.world);
+        let spatial_query = state.get(&app.world);
+        let filter = SpatialQueryFilter::default();
+
+        assert_eq!(
+            spatial_query.nearest(Vec2::ZERO, 1, f32::INFINITY, &filter),
+            vec![(alive, 60.)]
+        );
+        assert_eq!(
+            spatial_query.circle_query(Vec2::ZERO, 100., &filter),
+            vec![alive]
+        );
+        assert_eq!(
+            spatial_query
+                .ray_cast(Vec2::ZERO, Vec2::X, 100., &filter)
+                .map(|hit| hit.entity),
+            Some(alive)
+        );
+    }
+}
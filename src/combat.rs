@@ -3,39 +3,108 @@ use bevy::prelude::*;
 use crate::{animation::AppAnimationSetup, GameState};
 
 use self::{
-    fire::{fire_update, FireAnimation},
-    health::{check_death, DeathEvent, TookDamageEvent},
+    bomb::{bomb_update, create_bomb_rng, drop_bombs},
+    bounce::{bounce_off_edges, bounce_off_victims},
+    damage_numbers::{spawn_damage_numbers, update_damage_numbers},
+    explosion::{
+        detonate_explosions, spawn_explosions, trigger_explosions, update_explosions, ExplodeEvent,
+    },
+    fire::{burn_visuals, flaming_bullets, FireAnimation},
+    health::{check_death, regenerate_health, DeathEvent, TookDamageEvent},
     healthbar::{spawn_healthbars, update_healthbars},
-    knockback::knockback_update,
-    projectile::{projectile_collision_check, ProjectileHitEvent},
+    homing::homing_update,
+    iframes::{iframes_blink, iframes_update, GrantIFramesEvent},
+    knockback::{knockback_update, stagger_update},
+    poison::venomous_bullets,
+    projectile::{
+        projectile_collision_check, projectile_expiry, ProjectileExpiredEvent, ProjectileHitEvent,
+    },
+    status_effect::{
+        apply_status_effects, expire_status_effects_on_death, status_effect_tints,
+        status_effects_update, ApplyStatusEffectEvent, StatusEffectAppliedEvent,
+        StatusEffectExpiredEvent,
+    },
 };
 
+pub mod bomb;
+pub mod bounce;
+pub mod damage;
+pub mod damage_numbers;
+pub mod explosion;
 pub mod fire;
 pub mod health;
 pub mod healthbar;
+pub mod homing;
+pub mod iframes;
 pub mod knockback;
+pub mod poison;
 pub mod projectile;
+pub mod status_effect;
 pub mod teams;
 
 pub struct CombatPlugin;
 
 impl Plugin for CombatPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            Update,
-            (
-                projectile_collision_check,
-                update_healthbars,
-                check_death,
-                spawn_healthbars,
-                fire_update,
-                knockback_update.after(projectile_collision_check),
+        app.add_systems(OnEnter(GameState::Playing), create_bomb_rng)
+            .add_systems(
+                Update,
+                (
+                    projectile_collision_check,
+                    update_healthbars,
+                    check_death,
+                    spawn_healthbars,
+                    flaming_bullets.after(projectile_collision_check),
+                    venomous_bullets.after(projectile_collision_check),
+                    apply_status_effects
+                        .after(flaming_bullets)
+                        .after(venomous_bullets),
+                    status_effects_update.after(apply_status_effects),
+                    expire_status_effects_on_death
+                        .after(status_effects_update)
+                        .after(check_death),
+                    burn_visuals.after(expire_status_effects_on_death),
+                    status_effect_tints.after(status_effects_update),
+                    knockback_update.after(projectile_collision_check),
+                    spawn_damage_numbers.after(status_effects_update),
+                    update_damage_numbers,
+                    projectile_expiry.after(projectile_collision_check),
+                )
+                    .run_if(in_state(GameState::Playing)),
             )
-                .run_if(in_state(GameState::Playing)),
-        )
-        .add_animation::<FireAnimation>()
-        .add_event::<DeathEvent>()
-        .add_event::<TookDamageEvent>()
-        .add_event::<ProjectileHitEvent>();
+            .add_systems(
+                Update,
+                (
+                    bounce_off_victims.after(projectile_collision_check),
+                    bounce_off_edges.before(projectile_expiry),
+                    homing_update.after(bounce_off_victims),
+                    trigger_explosions
+                        .after(projectile_expiry)
+                        .after(check_death),
+                    spawn_explosions.after(trigger_explosions),
+                    detonate_explosions,
+                    update_explosions,
+                    drop_bombs,
+                    bomb_update.before(spawn_explosions),
+                    iframes_update
+                        .after(projectile_collision_check)
+                        .after(status_effects_update)
+                        .after(detonate_explosions),
+                    iframes_blink.after(iframes_update),
+                    regenerate_health.after(iframes_update),
+                    stagger_update.after(knockback_update),
+                )
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_animation::<FireAnimation>()
+            .add_event::<DeathEvent>()
+            .add_event::<TookDamageEvent>()
+            .add_event::<ProjectileHitEvent>()
+            .add_event::<ProjectileExpiredEvent>()
+            .add_event::<ExplodeEvent>()
+            .add_event::<GrantIFramesEvent>()
+            .add_event::<ApplyStatusEffectEvent>()
+            .add_event::<StatusEffectAppliedEvent>()
+            .add_event::<StatusEffectExpiredEvent>();
     }
 }
@@ -0,0 +1,115 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    constants::{SortingLayers, SCALING_VEC3},
+    enemies::enemy::EnemyDeathEvent,
+    loading::TextureAssets,
+    movement::pause::ActionPauseState,
+    palette::Palette,
+    player::Player,
+    util::rng::{GlobalSeed, RNG},
+};
+
+use super::{
+    damage::DamageInfo,
+    explosion::{ExplodeEvent, Explosion},
+    health::HealthType,
+    projectile::DamageTarget,
+    teams::Team,
+};
+
+const BOMB_DROP_CHANCE: f64 = 0.03;
+const BOMB_PICK_DISTANCE: f32 = 16.;
+// Bombs that aren't picked up disappear after a while
+const BOMB_DURATION: f32 = 15.;
+const BOMB_RADIUS: f32 = 150.;
+const BOMB_DAMAGE: HealthType = 30;
+const BOMB_KNOCKBACK: f32 = 400.;
+
+#[derive(Resource)]
+pub struct BombRNG(pub RNG);
+
+/*
+Dropped by enemies now and then, blows up everything around it when the player picks it up
+*/
+#[derive(Component)]
+pub struct Bomb(Timer);
+
+pub fn create_bomb_rng(seed: Res<GlobalSeed>, mut commands: Commands) {
+    commands.insert_resource(BombRNG(RNG::new(seed.0.as_str(), "bomb_rng")))
+}
+
+pub fn drop_bombs(
+    mut enemy_death_event: EventReader<EnemyDeathEvent>,
+    mut bomb_rng: ResMut<BombRNG>,
+    textures: Res<TextureAssets>,
+    palette: Res<Palette>,
+    mut commands: Commands,
+) {
+    for death_ev in enemy_death_event.iter() {
+        if !bomb_rng.0 .0.gen_bool(BOMB_DROP_CHANCE) {
+            continue;
+        }
+
+        // Shares the crystal sprite until it gets its own
+        commands
+            .spawn(SpriteBundle {
+                texture: textures.crystal.clone(),
+                sprite: Sprite {
+                    color: palette.red,
+                    ..default()
+                },
+                transform: Transform {
+                    translation: death_ev
+                        .location
+                        .truncate()
+                        .extend(SortingLayers::Action.into()),
+                    rotation: default(),
+                    scale: SCALING_VEC3 * 1.5,
+                },
+                ..default()
+            })
+            .insert(Bomb(Timer::from_seconds(BOMB_DURATION, TimerMode::Once)));
+    }
+}
+
+pub fn bomb_update(
+    mut q_bombs: Query<(Entity, &mut Bomb, &Transform), Without<Player>>,
+    q_player: Query<(Entity, &Transform), With<Player>>,
+    mut explode_ev: EventWriter<ExplodeEvent>,
+    pause: Res<ActionPauseState>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    if pause.is_paused {
+        return;
+    }
+
+    let (player_entity, player_transform) = q_player.single();
+
+    for (entity, mut bomb, transform) in q_bombs.iter_mut() {
+        bomb.0.tick(time.delta());
+
+        if bomb.0.finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        let position = transform.translation.truncate();
+        if position.distance(player_transform.translation.truncate()) > BOMB_PICK_DISTANCE {
+            continue;
+        }
+
+        commands.entity(entity).despawn();
+        explode_ev.send(ExplodeEvent {
+            position,
+            explosion: Explosion::new(
+                BOMB_RADIUS,
+                DamageInfo::physical(BOMB_DAMAGE).with_source(player_entity),
+                DamageTarget::Team(Team::Enemy),
+            )
+            .with_knockback(BOMB_KNOCKBACK),
+        });
+    }
+}
@@ -0,0 +1,165 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{
+    collision::{
+        collider::{Collider, ColliderPose, ContinuousCollision},
+        geometry::contact_normal,
+        layers::CollisionLayer,
+        spatial_query::{SpatialQuery, SpatialQueryFilter},
+    },
+    movement::{pause::ActionPauseState, velocity::Velocity},
+};
+
+use super::{
+    projectile::{Projectile, ProjectileHitEvent},
+    teams::Team,
+};
+
+// How far a bouncing projectile looks for its next target
+const RETARGET_RANGE: f32 = 300.;
+
+/*
+Projectiles with bounces reflect off the arena edges and obstacles,
+and survive hitting something they would otherwise be destroyed by
+*/
+#[derive(Component)]
+pub struct Bounces {
+    pub remaining: u32,
+    // Turns towards the nearest enemy that wasn't hit yet after hitting one, instead of bouncing off it
+    pub retarget: bool,
+}
+
+impl Bounces {
+    pub fn new(count: u32) -> Bounces {
+        Bounces {
+            remaining: count,
+            retarget: false,
+        }
+    }
+
+    pub fn with_retarget(mut self) -> Bounces {
+        self.retarget = true;
+        self
+    }
+
+    // Uses up a bounce, false if there are none left
+    pub fn consume(&mut self) -> bool {
+        if self.remaining == 0 {
+            return false;
+        }
+
+        self.remaining -= 1;
+        true
+    }
+}
+
+// Mirrors the velocity on the surface with the normal
+pub fn reflect(velocity: Vec2, normal: Vec2) -> Vec2 {
+    velocity - 2. * velocity.dot(normal) * normal
+}
+
+pub fn bounce_off_edges(
+    mut q_projectiles: Query<(&mut Bounces, &mut Velocity, &Transform)>,
+    q_windows: Query<&Window, With<PrimaryWindow>>,
+    pause: Res<ActionPauseState>,
+) {
+    if pause.is_paused {
+        return;
+    }
+
+    let window = q_windows.single();
+    let half_arena = Vec2::new(window.width(), window.height()) / 2.;
+
+    for (mut bounces, mut velocity, transform) in q_projectiles.iter_mut() {
+        let position = transform.translation.truncate();
+
+        // Only bounce when moving further out, so it can't get stuck in the edge
+        let normal = if position.x > half_arena.x && velocity.vec.x > 0. {
+            Vec2::NEG_X
+        } else if position.x < -half_arena.x && velocity.vec.x < 0. {
+            Vec2::X
+        } else if position.y > half_arena.y && velocity.vec.y > 0. {
+            Vec2::NEG_Y
+        } else if position.y < -half_arena.y && velocity.vec.y < 0. {
+            Vec2::Y
+        } else {
+            continue;
+        };
+
+        if bounces.consume() {
+            velocity.vec = reflect(velocity.vec, normal);
+        }
+    }
+}
+
+// Projectiles that bounced off an enemy turn towards the next one if they retarget,
+// otherwise they are mirrored on the enemy's surface
+pub fn bounce_off_victims(
+    mut q_projectiles: Query<(
+        &Projectile,
+        &Bounces,
+        &mut Velocity,
+        &Collider,
+        &mut Transform,
+        Option<&mut ContinuousCollision>,
+    )>,
+    q_bodies: Query<(&Collider, &Transform), Without<Projectile>>,
+    mut ev_hit: EventReader<ProjectileHitEvent>,
+    spatial_query: SpatialQuery,
+) {
+    for hit in ev_hit.iter() {
+        if !hit.bounced {
+            continue;
+        }
+
+        let Ok((projectile, bounces, mut velocity, collider, mut transform, continuous)) =
+            q_projectiles.get_mut(hit.projectile)
+        else {
+            continue;
+        };
+
+        if !projectile.is_alive {
+            continue;
+        }
+
+        // Fast projectiles may have passed the enemy already, they bounce where they hit it
+        if let (Some(mut continuous), Some(time_of_impact)) = (continuous, hit.time_of_impact) {
+            continuous.rewind(time_of_impact, &mut transform);
+        }
+
+        let position = transform.translation.truncate();
+
+        if bounces.retarget {
+            let filter = SpatialQueryFilter {
+                excluded: projectile.entities_hit.clone(),
+                ..default()
+            }
+            .with_team(Team::Enemy)
+            .with_layers(&[CollisionLayer::Enemy]);
+
+            let target = spatial_query
+                .nearest(position, 1, RETARGET_RANGE, &filter)
+                .first()
+                .and_then(|(target, _)| q_bodies.get(*target).ok());
+
+            if let Some((_, target_transform)) = target {
+                let direction =
+                    (target_transform.translation.truncate() - position).normalize_or_zero();
+                if direction != Vec2::ZERO {
+                    velocity.vec = direction * velocity.vec.length();
+                    continue;
+                }
+            }
+        }
+
+        // Straight back if the victim is already gone
+        let normal = match q_bodies.get(hit.victim) {
+            Ok((victim_collider, victim_transform)) => contact_normal(
+                &collider.core(ColliderPose::from(transform.as_ref())),
+                &victim_collider.core(ColliderPose::from(victim_transform)),
+            ),
+            Err(_) => -velocity.vec.normalize_or_zero(),
+        };
+        velocity.vec = reflect(velocity.vec, normal);
+    }
+}
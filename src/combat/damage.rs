@@ -0,0 +1,102 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::player::ability::Ability;
+
+use super::health::HealthType;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum DamageType {
+    Physical,
+    Fire,
+    Poison,
+    // Reflected by the player's thorns
+    Thorns,
+}
+
+/*
+Everything known about a single instance of damage, from the hit until it is applied
+*/
+#[derive(Clone, Copy, Debug)]
+pub struct DamageInfo {
+    pub amount: HealthType,
+    pub damage_type: DamageType,
+    // The entity responsible for the damage, e.g. the player for their bullets
+    pub source: Option<Entity>,
+    // The ability that caused the damage, if any
+    pub ability: Option<Ability>,
+    pub is_crit: bool,
+    // Added to the velocity of whoever takes the damage
+    pub knockback: Vec2,
+    // Periodic damage like burning or thorns, it doesn't grant invulnerability frames
+    pub is_tick: bool,
+}
+
+impl DamageInfo {
+    pub fn new(amount: HealthType, damage_type: DamageType) -> DamageInfo {
+        DamageInfo {
+            amount,
+            damage_type,
+            source: None,
+            ability: None,
+            is_crit: false,
+            knockback: Vec2::ZERO,
+            is_tick: false,
+        }
+    }
+
+    pub fn physical(amount: HealthType) -> DamageInfo {
+        DamageInfo::new(amount, DamageType::Physical)
+    }
+
+    pub fn with_source(mut self, source: Entity) -> DamageInfo {
+        self.source = Some(source);
+        self
+    }
+
+    pub fn with_ability(mut self, ability: Ability) -> DamageInfo {
+        self.ability = Some(ability);
+        self
+    }
+
+    pub fn with_knockback(mut self, knockback: Vec2) -> DamageInfo {
+        self.knockback = knockback;
+        self
+    }
+
+    pub fn as_tick(mut self) -> DamageInfo {
+        self.is_tick = true;
+        self
+    }
+
+    pub fn crit(mut self, multiplier: f32) -> DamageInfo {
+        self.amount = (self.amount as f32 * multiplier).round() as HealthType;
+        self.is_crit = true;
+        self
+    }
+}
+
+// Crits also hit harder
+pub const CRIT_KNOCKBACK_MULTIPLIER: f32 = 1.5;
+
+/*
+Reduces incoming damage per damage type, 0.25 takes 25% less damage
+and negative values make the entity weak to that type
+*/
+#[derive(Component, Default, Clone)]
+pub struct Resistances(HashMap<DamageType, f32>);
+
+impl Resistances {
+    pub fn with(mut self, damage_type: DamageType, resistance: f32) -> Resistances {
+        self.0.insert(damage_type, resistance);
+        self
+    }
+
+    pub fn get(&self, damage_type: DamageType) -> f32 {
+        self.0.get(&damage_type).copied().unwrap_or(0.)
+    }
+
+    pub fn apply(&self, damage: &DamageInfo) -> HealthType {
+        let multiplier = (1. - self.get(damage.damage_type)).max(0.);
+        (damage.amount as f32 * multiplier).round() as HealthType
+    }
+}
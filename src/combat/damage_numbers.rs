@@ -0,0 +1,222 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{
+    constants::SortingLayers, loading::FontAssets, movement::pause::ActionPauseState,
+    palette::Palette,
+};
+
+use super::{
+    damage::DamageType,
+    health::{HealthType, TookDamageEvent},
+};
+
+const LIFETIME: f32 = 0.8;
+// Hits on the same entity within this window are added to the same number
+const AGGREGATION_WINDOW: f32 = 0.25;
+const RISE_SPEED: f32 = 40.;
+const FONT_SIZE: f32 = 18.;
+const CRIT_FONT_SIZE: f32 = 28.;
+const OFFSET: Vec3 = Vec3 {
+    x: 0.,
+    y: 20.,
+    z: 0.,
+};
+
+#[derive(Component)]
+pub struct DamageNumber {
+    target: Entity,
+    damage_type: DamageType,
+    amount: HealthType,
+    age: Timer,
+}
+
+impl DamageNumber {
+    fn is_aggregating(&self) -> bool {
+        self.age.elapsed_secs() < AGGREGATION_WINDOW
+    }
+}
+
+fn damage_type_color(damage_type: DamageType, palette: &Palette) -> Color {
+    match damage_type {
+        DamageType::Physical => palette.white,
+        DamageType::Fire => palette.orange,
+        DamageType::Poison => Color::rgb(0.6, 0.85, 0.4),
+        DamageType::Thorns => palette.red,
+    }
+}
+
+pub fn spawn_damage_numbers(
+    q_targets: Query<&Transform, Without<DamageNumber>>,
+    mut q_numbers: Query<(&mut DamageNumber, &mut Text)>,
+    mut ev_dmg: EventReader<TookDamageEvent>,
+    font_assets: Res<FontAssets>,
+    palette: Res<Palette>,
+    mut commands: Commands,
+) {
+    // Hits of the same frame are added up first, numbers spawned this frame can't be found below
+    let mut frame_hits: HashMap<(Entity, DamageType), (HealthType, bool)> = HashMap::new();
+    for took_dmg in ev_dmg.iter() {
+        if took_dmg.amount == 0 {
+            continue;
+        }
+
+        let (amount, is_crit) = frame_hits
+            .entry((took_dmg.entity, took_dmg.damage.damage_type))
+            .or_insert((0, false));
+        *amount += took_dmg.amount;
+        *is_crit |= took_dmg.damage.is_crit;
+    }
+
+    for ((target, damage_type), (amount, is_crit)) in frame_hits {
+        // Shotgun volleys add up instead of stacking a wall of numbers
+        if let Some((mut number, mut text)) = q_numbers.iter_mut().find(|(number, _)| {
+            number.target == target && number.damage_type == damage_type && number.is_aggregating()
+        }) {
+            number.amount += amount;
+            text.sections[0].value = number.amount.to_string();
+
+            if is_crit {
+                text.sections[0].style.font_size = CRIT_FONT_SIZE;
+            }
+            continue;
+        }
+
+        let Ok(target_transform) = q_targets.get(target) else {
+            continue;
+        };
+
+        commands
+            .spawn(Text2dBundle {
+                text: Text::from_section(
+                    amount.to_string(),
+                    TextStyle {
+                        font: font_assets.gothic_pxl.clone(),
+                        font_size: match is_crit {
+                            true => CRIT_FONT_SIZE,
+                            false => FONT_SIZE,
+                        },
+                        color: damage_type_color(damage_type, &palette),
+                    },
+                ),
+                transform: Transform::from_translation(
+                    target_transform
+                        .translation
+                        .truncate()
+                        .extend(SortingLayers::UI.into())
+                        + OFFSET,
+                ),
+                ..default()
+            })
+            .insert(DamageNumber {
+                target,
+                damage_type,
+                amount,
+                age: Timer::from_seconds(LIFETIME, TimerMode::Once),
+            });
+    }
+}
+
+// Numbers rise and fade out
+pub fn update_damage_numbers(
+    mut q_numbers: Query<(Entity, &mut DamageNumber, &mut Transform, &mut Text)>,
+    pause: Res<ActionPauseState>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    if pause.is_paused {
+        return;
+    }
+
+    for (entity, mut number, mut transform, mut text) in q_numbers.iter_mut() {
+        number.age.tick(time.delta());
+
+        if number.age.finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        transform.translation.y += RISE_SPEED * time.delta_seconds();
+
+        let alpha = number.age.percent_left();
+        for section in text.sections.iter_mut() {
+            section.style.color.set_a(alpha);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::combat::damage::DamageInfo;
+
+    fn damage_number_app() -> App {
+        let mut app = App::new();
+        app.add_event::<TookDamageEvent>()
+            .insert_resource(FontAssets {
+                fira_sans: default(),
+                gothic: default(),
+                gothic_pxl: default(),
+            })
+            .insert_resource(Palette {
+                dark: Color::BLACK,
+                red: Color::RED,
+                orange: Color::ORANGE,
+                white: Color::WHITE,
+            })
+            .add_systems(Update, spawn_damage_numbers);
+        app
+    }
+
+    fn hit(app: &mut App, entity: Entity, damage: DamageInfo) {
+        app.world.send_event(TookDamageEvent {
+            entity,
+            amount: damage.amount,
+            absorbed: 0,
+            damage,
+        });
+    }
+
+    fn numbers(app: &mut App) -> Vec<(Entity, DamageType, HealthType)> {
+        let mut numbers: Vec<_> = app
+            .world
+            .query::<&DamageNumber>()
+            .iter(&app.world)
+            .map(|number| (number.target, number.damage_type, number.amount))
+            .collect();
+        numbers.sort_by_key(|(_, damage_type, _)| *damage_type as u8);
+        numbers
+    }
+
+    #[test]
+    fn hits_of_the_same_frame_add_up() {
+        let mut app = damage_number_app();
+        let target = app.world.spawn(Transform::default()).id();
+
+        // A shotgun volley, with a burn tick on top
+        for _ in 0..5 {
+            hit(&mut app, target, DamageInfo::physical(2));
+        }
+        hit(&mut app, target, DamageInfo::new(1, DamageType::Fire));
+        app.update();
+
+        assert_eq!(
+            numbers(&mut app),
+            vec![
+                (target, DamageType::Physical, 10),
+                (target, DamageType::Fire, 1)
+            ]
+        );
+
+        // The next frame is still within the window and adds to the same number
+        hit(&mut app, target, DamageInfo::physical(3));
+        app.update();
+
+        assert_eq!(
+            numbers(&mut app),
+            vec![
+                (target, DamageType::Physical, 13),
+                (target, DamageType::Fire, 1)
+            ]
+        );
+    }
+}
@@ -0,0 +1,261 @@
+use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
+
+use crate::{
+    collision::spatial_query::{SpatialQuery, SpatialQueryFilter},
+    constants::SortingLayers,
+    movement::pause::ActionPauseState,
+    palette::Palette,
+};
+
+use super::{
+    damage::{DamageInfo, Resistances},
+    health::{DeathEvent, Health, HealthType, TookDamageEvent},
+    projectile::{DamageTarget, ExpiryReason, ProjectileExpiredEvent, ProjectileHitEvent},
+    status_effect::StatusEffects,
+    teams::{Team, TeamMember},
+};
+
+// How long the blast stays visible
+const EXPLOSION_DURATION: f32 = 0.3;
+// Damage and knockback at the edge of the radius compared to the center
+const EDGE_FALLOFF: f32 = 0.3;
+
+/*
+Damages everything of the target team within the radius once, the closer the harder
+*/
+#[derive(Component, Clone, Copy)]
+pub struct Explosion {
+    pub radius: f32,
+    pub damage: DamageInfo,
+    pub damage_target: DamageTarget,
+    // Pushes victims away from the center
+    pub knockback: f32,
+}
+
+impl Explosion {
+    pub fn new(radius: f32, damage: DamageInfo, damage_target: DamageTarget) -> Explosion {
+        Explosion {
+            radius,
+            damage,
+            damage_target,
+            knockback: 0.,
+        }
+    }
+
+    pub fn with_knockback(mut self, knockback: f32) -> Explosion {
+        self.knockback = knockback;
+        self
+    }
+
+    // 1 at the center down to `EDGE_FALLOFF` at the radius
+    fn falloff(&self, distance: f32) -> f32 {
+        let t = (distance / self.radius).clamp(0., 1.);
+        1. - (1. - EDGE_FALLOFF) * t
+    }
+
+    fn can_hit(&self, team: Team) -> bool {
+        match self.damage_target {
+            DamageTarget::All => true,
+            DamageTarget::Team(target_team) => team == target_team || team == Team::None,
+        }
+    }
+}
+
+/*
+Makes the entity explode when it hits something, expires or dies
+*/
+#[derive(Component, Clone, Copy)]
+pub struct Explodes {
+    pub explosion: Explosion,
+    pub on_hit: bool,
+    pub on_expiry: bool,
+    pub on_death: bool,
+}
+
+impl Explodes {
+    pub fn new(explosion: Explosion) -> Explodes {
+        Explodes {
+            explosion,
+            on_hit: false,
+            on_expiry: false,
+            on_death: false,
+        }
+    }
+
+    pub fn on_hit(mut self) -> Explodes {
+        self.on_hit = true;
+        self
+    }
+
+    pub fn on_expiry(mut self) -> Explodes {
+        self.on_expiry = true;
+        self
+    }
+
+    pub fn on_death(mut self) -> Explodes {
+        self.on_death = true;
+        self
+    }
+}
+
+#[derive(Event)]
+pub struct ExplodeEvent {
+    pub position: Vec2,
+    pub explosion: Explosion,
+}
+
+#[derive(Component)]
+pub struct ExplosionVisual(Timer);
+
+pub fn trigger_explosions(
+    q_explodes: Query<(&Explodes, &Transform)>,
+    mut hit_ev: EventReader<ProjectileHitEvent>,
+    mut expired_ev: EventReader<ProjectileExpiredEvent>,
+    mut death_ev: EventReader<DeathEvent>,
+    mut explode_ev: EventWriter<ExplodeEvent>,
+) {
+    for hit in hit_ev.iter() {
+        if let Ok((explodes, transform)) = q_explodes.get(hit.projectile) {
+            if explodes.on_hit {
+                explode_ev.send(ExplodeEvent {
+                    position: transform.translation.truncate(),
+                    explosion: explodes.explosion,
+                });
+            }
+        }
+    }
+
+    for expired in expired_ev.iter() {
+        // Nobody would see it
+        if expired.reason == ExpiryReason::LeftArena {
+            continue;
+        }
+
+        if let Ok((explodes, _)) = q_explodes.get(expired.projectile) {
+            if explodes.on_expiry {
+                explode_ev.send(ExplodeEvent {
+                    position: expired.position,
+                    explosion: explodes.explosion,
+                });
+            }
+        }
+    }
+
+    for death in death_ev.iter() {
+        if let Ok((explodes, transform)) = q_explodes.get(death.entity) {
+            if explodes.on_death {
+                explode_ev.send(ExplodeEvent {
+                    position: transform.translation.truncate(),
+                    explosion: explodes.explosion,
+                });
+            }
+        }
+    }
+}
+
+pub fn spawn_explosions(
+    mut explode_ev: EventReader<ExplodeEvent>,
+    palette: Res<Palette>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut commands: Commands,
+) {
+    for ev in explode_ev.iter() {
+        commands
+            .spawn(MaterialMesh2dBundle {
+                mesh: meshes
+                    .add(shape::Circle::new(ev.explosion.radius).into())
+                    .into(),
+                material: materials.add(ColorMaterial::from(palette.orange)),
+                transform: Transform::from_translation(
+                    ev.position.extend(SortingLayers::Front.into()),
+                ),
+                ..default()
+            })
+            .insert(ev.explosion)
+            .insert(ExplosionVisual(Timer::from_seconds(
+                EXPLOSION_DURATION,
+                TimerMode::Once,
+            )));
+    }
+}
+
+// Explosions deal their damage in the first frame they exist
+pub fn detonate_explosions(
+    q_explosions: Query<(Entity, &Explosion, &Transform), Added<Explosion>>,
+    mut q_hittable: Query<
+        (
+            &mut Health,
+            &TeamMember,
+            &Transform,
+            Option<&Resistances>,
+            Option<&StatusEffects>,
+        ),
+        Without<Explosion>,
+    >,
+    spatial_query: SpatialQuery,
+    mut ev_hit: EventWriter<ProjectileHitEvent>,
+    mut ev_dmg: EventWriter<TookDamageEvent>,
+) {
+    for (explosion_entity, explosion, transform) in q_explosions.iter() {
+        let center = transform.translation.truncate();
+
+        for victim in
+            spatial_query.circle_query(center, explosion.radius, &SpatialQueryFilter::default())
+        {
+            let Ok((mut health, member, victim_transform, resistances, status_effects)) =
+                q_hittable.get_mut(victim)
+            else {
+                continue;
+            };
+
+            if !health.is_alive() || !explosion.can_hit(member.team) {
+                continue;
+            }
+
+            let offset = victim_transform.translation.truncate() - center;
+            let falloff = explosion.falloff(offset.length());
+
+            let mut damage = explosion
+                .damage
+                .with_knockback(offset.normalize_or_zero() * explosion.knockback * falloff);
+            damage.amount = ((damage.amount as f32 * falloff).round() as HealthType).max(1);
+
+            health.take_damage(victim, &mut ev_dmg, damage, resistances, status_effects);
+
+            // On-hit effects treat the explosion like a projectile
+            ev_hit.send(ProjectileHitEvent {
+                projectile: explosion_entity,
+                victim,
+                time_of_impact: None,
+                bounced: false,
+            });
+        }
+    }
+}
+
+// The blast fades out
+pub fn update_explosions(
+    mut q_explosions: Query<(Entity, &mut ExplosionVisual, &Handle<ColorMaterial>)>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    pause: Res<ActionPauseState>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    if pause.is_paused {
+        return;
+    }
+
+    for (entity, mut visual, material) in q_explosions.iter_mut() {
+        visual.0.tick(time.delta());
+
+        if visual.0.finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        if let Some(material) = materials.get_mut(material) {
+            material.color.set_a(visual.0.percent_left() * 0.8);
+        }
+    }
+}
@@ -1,7 +1,6 @@
 use std::time::Duration;
 
-use bevy::{prelude::*, transform::commands};
-use bevy_debug_text_overlay::screen_print;
+use bevy::prelude::*;
 
 use crate::{
     animation::{
@@ -13,14 +12,19 @@ use crate::{
 };
 
 use super::{
-    health::{Health, HealthType, TookDamageEvent},
     projectile::ProjectileHitEvent,
+    status_effect::{
+        ApplyStatusEffectEvent, StatusEffect, StatusEffectAppliedEvent, StatusEffectExpiredEvent,
+        StatusEffectKind,
+    },
 };
 
+const BURN_DURATION: f32 = 4.;
+
+// The fire animation on a burning entity
 #[derive(Component)]
 pub struct Fire {
     pub parent: Entity,
-    pub timer: Timer,
 }
 
 #[derive(PartialEq, Eq, Hash, Clone, Copy)]
@@ -36,37 +40,58 @@ impl Animation<FireAnimation> for FireAnimation {
     }
 }
 
-pub fn fire_update(
-    mut q_fire: Query<(&mut Fire, &Parent), Without<Health>>,
-    mut q_health: Query<(Entity, &mut Health), With<Enemy>>,
-    q_player: Query<&Player, (Without<Enemy>, Without<Fire>)>,
-    mut took_damage_ev: EventWriter<TookDamageEvent>,
+// Enemies hit while the player has flaming bullets start burning
+pub fn flaming_bullets(
+    q_enemies: Query<(), With<Enemy>>,
+    q_player: Query<(Entity, &Player)>,
     mut projectile_hit: EventReader<ProjectileHitEvent>,
+    mut apply_ev: EventWriter<ApplyStatusEffectEvent>,
+) {
+    let (player_entity, player) = q_player.single();
+
+    if !player.abilities.contains(&Ability::FlamingBullets) {
+        return;
+    }
+
+    let hotter = player
+        .abilities
+        .iter()
+        .filter(|ability| ability == &&Ability::HotterFire)
+        .count() as f32;
+
+    for proj_hit in projectile_hit.iter() {
+        if !q_enemies.contains(proj_hit.victim) {
+            continue;
+        }
+
+        apply_ev.send(ApplyStatusEffectEvent {
+            target: proj_hit.victim,
+            effect: StatusEffect::new(StatusEffectKind::Burn, 1. + hotter, BURN_DURATION)
+                .with_source(player_entity)
+                .with_ability(Ability::FlamingBullets),
+        });
+    }
+}
+
+// Burning entities get a fire animation as a child for as long as they burn
+pub fn burn_visuals(
+    q_fire: Query<(Entity, &Fire)>,
+    mut applied_ev: EventReader<StatusEffectAppliedEvent>,
+    mut expired_ev: EventReader<StatusEffectExpiredEvent>,
     animations: Res<AnimationStateStorage<FireAnimation>>,
     textures: Res<TextureAssets>,
     mut texture_atlases: ResMut<Assets<TextureAtlas>>,
-    time: Res<Time>,
     mut commands: Commands,
 ) {
-    let player = q_player.single();
-
-    for (mut fire, parent) in q_fire.iter_mut() {
-        fire.timer.tick(time.delta());
-
-        if !fire.timer.just_finished() {
+    for expired in expired_ev.iter() {
+        if expired.kind != StatusEffectKind::Burn {
             continue;
         }
 
-        if let Ok((_, mut health)) = q_health.get_mut(parent.get()) {
-            let base_damage: HealthType = 2;
-            let hotter = player
-                .abilities
-                .iter()
-                .filter(|ability| ability == &&Ability::HotterFire)
-                .count() as HealthType;
-            let damage = base_damage + hotter * 2;
-
-            health.take_damage(parent.get(), &mut took_damage_ev, damage)
+        for (fire_entity, fire) in q_fire.iter() {
+            if fire.parent == expired.entity {
+                commands.entity(fire_entity).despawn();
+            }
         }
     }
 
@@ -80,34 +105,28 @@ pub fn fire_update(
     );
     let texture_atlas_handle = texture_atlases.add(texture_atlas);
 
-    if !player.abilities.contains(&Ability::FlamingBullets) {
-        return;
-    }
+    for applied in applied_ev.iter() {
+        if applied.kind != StatusEffectKind::Burn || !applied.is_new {
+            continue;
+        }
 
-    for proj_hit in projectile_hit.iter() {
-        if let Ok((entity, _)) = q_health.get(proj_hit.victim) {
-            // don't set fire twice
-            if q_fire.iter().any(|(fire, _)| fire.parent == entity) {
-                continue;
-            }
+        let parent = applied.entity;
 
-            commands
-                .spawn(make_animation_bundle(
-                    FireAnimation::Fire,
-                    &animations,
-                    texture_atlas_handle.clone(),
-                    Vec3::ZERO,
-                    0.5,
-                ))
-                .insert(Fire {
-                    parent: entity,
-                    timer: Timer::from_seconds(2.0, TimerMode::Repeating),
-                })
-                .add(|id, world: &mut World| {
-                    if let Some(fire) = world.entity(id).get::<Fire>() {
-                        world.entity_mut(fire.parent).add_child(id);
-                    }
-                });
-        }
+        commands
+            .spawn(make_animation_bundle(
+                FireAnimation::Fire,
+                &animations,
+                texture_atlas_handle.clone(),
+                Vec3::ZERO,
+                0.5,
+            ))
+            .insert(Fire { parent })
+            .add(move |id, world: &mut World| {
+                if let Some(mut parent) = world.get_entity_mut(parent) {
+                    parent.add_child(id);
+                } else {
+                    world.despawn(id); // Died before the fire could catch
+                }
+            });
     }
 }
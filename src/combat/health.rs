@@ -1,18 +1,84 @@
 use bevy::prelude::*;
 
+use crate::movement::pause::ActionPauseState;
+
+use super::{
+    damage::{DamageInfo, Resistances},
+    status_effect::StatusEffects,
+};
+
 pub type HealthType = u32;
 
+/*
+Absorbs damage before health does and recharges after not taking damage for a while
+*/
+#[derive(Clone)]
+pub struct Shield {
+    pub value: HealthType,
+    pub max: HealthType,
+    // Seconds without taking damage until it starts recharging
+    pub recharge_delay: f32,
+    // Seconds per recharged point
+    pub recharge_time: f32,
+    since_hit: f32,
+    recharge_progress: f32,
+}
+
+impl Shield {
+    pub fn new(max: HealthType, recharge_delay: f32, recharge_time: f32) -> Shield {
+        Shield {
+            value: max,
+            max,
+            recharge_delay,
+            recharge_time,
+            since_hit: 0.,
+            recharge_progress: 0.,
+        }
+    }
+
+    // Returns the damage that got through
+    fn absorb(&mut self, dmg: HealthType) -> HealthType {
+        self.since_hit = 0.;
+        self.recharge_progress = 0.;
+
+        let absorbed = dmg.min(self.value);
+        self.value -= absorbed;
+        dmg - absorbed
+    }
+
+    fn recharge(&mut self, delta: f32) {
+        self.since_hit += delta;
+
+        if self.value >= self.max || self.since_hit < self.recharge_delay {
+            return;
+        }
+
+        self.recharge_progress += delta / self.recharge_time;
+        while self.recharge_progress >= 1. && self.value < self.max {
+            self.recharge_progress -= 1.;
+            self.value += 1;
+        }
+    }
+}
+
 #[derive(Component, Clone)]
 pub struct Health {
     pub value: HealthType,
     pub max: HealthType,
     pub is_invincible: bool,
+    pub shield: Option<Shield>,
+    // Health regenerated per second
+    pub regeneration: f32,
+    regen_progress: f32,
 }
 
 #[derive(Event)]
 pub struct TookDamageEvent {
     pub entity: Entity,
+    // What was actually taken after resistances, including what the shield absorbed
     pub amount: HealthType,
+    pub absorbed: HealthType,
+    pub damage: DamageInfo,
 }
 
 impl Health {
@@ -21,28 +87,64 @@ impl Health {
             value: max,
             max,
             is_invincible: false,
+            shield: None,
+            regeneration: 0.,
+            regen_progress: 0.,
         }
     }
 
+    pub fn with_shield(mut self, shield: Shield) -> Health {
+        self.shield = Some(shield);
+        self
+    }
+
+    pub fn shield_value(&self) -> HealthType {
+        self.shield.as_ref().map_or(0, |shield| shield.value)
+    }
+
+    pub fn shield_max(&self) -> HealthType {
+        self.shield.as_ref().map_or(0, |shield| shield.max)
+    }
+
     pub fn take_damage(
         &mut self,
         my_entity: Entity,
         took_damage_ev: &mut EventWriter<TookDamageEvent>,
-        dmg: HealthType,
+        damage: DamageInfo,
+        resistances: Option<&Resistances>,
+        status_effects: Option<&StatusEffects>,
     ) {
         if self.is_invincible {
             return;
         }
 
-        if dmg > self.value {
+        // Vulnerability scales the damage before resistances reduce it
+        let damage = match status_effects {
+            Some(status_effects) => status_effects.amplify(damage),
+            None => damage,
+        };
+
+        let dmg = match resistances {
+            Some(resistances) => resistances.apply(&damage),
+            None => damage.amount,
+        };
+
+        let through = match self.shield.as_mut() {
+            Some(shield) => shield.absorb(dmg),
+            None => dmg,
+        };
+
+        if through > self.value {
             self.value = 0;
         } else {
-            self.value -= dmg;
+            self.value -= through;
         }
 
         took_damage_ev.send(TookDamageEvent {
             entity: my_entity,
             amount: dmg,
+            absorbed: dmg - through,
+            damage,
         });
     }
 
@@ -51,6 +153,9 @@ impl Health {
     }
 }
 
+/*
+Inserted once an entity runs out of health, it is ignored by collisions and AI from then on
+*/
 #[derive(Component)]
 pub struct Dead;
 
@@ -62,11 +167,45 @@ pub struct DeathEvent {
 pub fn check_death(
     q_health: Query<(Entity, &Health), Without<Dead>>,
     mut death_ev: EventWriter<DeathEvent>,
-    _commands: Commands,
+    mut commands: Commands,
 ) {
     for (entity, health) in q_health.iter() {
         if !health.is_alive() {
+            commands.entity(entity).insert(Dead);
             death_ev.send(DeathEvent { entity });
         }
     }
 }
+
+pub fn regenerate_health(
+    mut q_health: Query<&mut Health, Without<Dead>>,
+    pause: Res<ActionPauseState>,
+    time: Res<Time>,
+) {
+    if pause.is_paused {
+        return;
+    }
+
+    let delta = time.delta_seconds();
+
+    for mut health in q_health.iter_mut() {
+        if health.shield.is_none() && health.regeneration == 0. {
+            continue;
+        }
+
+        if let Some(shield) = health.shield.as_mut() {
+            shield.recharge(delta);
+        }
+
+        if health.value >= health.max {
+            health.regen_progress = 0.;
+            continue;
+        }
+
+        health.regen_progress += health.regeneration * delta;
+        while health.regen_progress >= 1. && health.value < health.max {
+            health.regen_progress -= 1.;
+            health.value += 1;
+        }
+    }
+}
@@ -2,9 +2,10 @@ use bevy::prelude::*;
 
 use crate::{constants::SCALING_VEC3, loading::TextureAssets};
 
-use super::health::Health;
+use super::health::{Dead, Health, HealthType};
 
 const HEALTH_BAR_SEGMENTS: usize = 15;
+const SHIELD_BAR_COLOR: Color = Color::rgba(0.5, 0.8, 1., 0.7);
 
 // If added to a component, the system will spawn a healthbar for it
 #[derive(Component)]
@@ -36,6 +37,17 @@ pub struct HealthBar {
     entity: Entity,
     is_alive: bool,
     offset: Vec2,
+    // Drawn over the healthbar and shows the shield instead
+    is_shield: bool,
+}
+
+// 0 is a full bar and the last segment an empty one
+fn segment_index(value: HealthType, max: HealthType) -> usize {
+    match value {
+        _ if value >= max => 0,
+        0 => HEALTH_BAR_SEGMENTS - 1,
+        _ => HEALTH_BAR_SEGMENTS - 2 - (value as usize * (HEALTH_BAR_SEGMENTS - 2)) / max as usize,
+    }
 }
 
 pub fn spawn_healthbars(
@@ -63,7 +75,7 @@ pub fn spawn_healthbars(
 
         commands
             .spawn(SpriteSheetBundle {
-                texture_atlas: texture_atlas_handle,
+                texture_atlas: texture_atlas_handle.clone(),
                 sprite: TextureAtlasSprite::new(0),
                 transform: Transform {
                     translation: Vec3::new(1000., 1000., 1.),
@@ -76,7 +88,32 @@ pub fn spawn_healthbars(
                 entity,
                 is_alive: true,
                 offset: Vec2 { x: 0., y: 0. },
+                is_shield: false,
+            });
+
+        // Always there and only shown while a shield holds, shields can be gained later on
+        commands
+            .spawn(SpriteSheetBundle {
+                texture_atlas: texture_atlas_handle,
+                sprite: TextureAtlasSprite {
+                    color: SHIELD_BAR_COLOR,
+                    ..TextureAtlasSprite::new(0)
+                },
+                transform: Transform {
+                    translation: Vec3::new(1000., 1000., 1.1),
+                    rotation: Quat::IDENTITY,
+                    scale: SCALING_VEC3,
+                },
+                visibility: Visibility::Hidden,
+                ..Default::default()
+            })
+            .insert(HealthBar {
+                entity,
+                is_alive: true,
+                offset: Vec2 { x: 0., y: 0. },
+                is_shield: true,
             });
+
         needs_health_bar.is_done = true;
     }
 }
@@ -87,12 +124,19 @@ pub fn update_healthbars(
         &mut Transform,
         &mut HealthBar,
         &mut TextureAtlasSprite,
+        &mut Visibility,
     )>,
-    q_entities: Query<(Entity, &Transform, &Health), Without<HealthBar>>,
+    // Dead entities lose their healthbar right away
+    q_entities: Query<(Entity, &Transform, &Health), (Without<HealthBar>, Without<Dead>)>,
     mut commands: Commands,
 ) {
-    for (healthbar_entity, mut healthbar_transform, mut healthbar, mut sprite_atlas) in
-        q_healthbars.iter_mut()
+    for (
+        healthbar_entity,
+        mut healthbar_transform,
+        mut healthbar,
+        mut sprite_atlas,
+        mut visibility,
+    ) in q_healthbars.iter_mut()
     {
         if let Ok((_, parent_transform, health)) = q_entities.get(healthbar.entity) {
             let translation_2d = parent_transform.translation.truncate() + healthbar.offset;
@@ -102,16 +146,16 @@ pub fn update_healthbars(
                 z: healthbar_transform.translation.z,
             };
 
-            let index = match health.value {
-                _ if health.value == health.max => 0,
-                _ if health.value == 0 => HEALTH_BAR_SEGMENTS - 1,
-                _ => {
-                    HEALTH_BAR_SEGMENTS
-                        - 2
-                        - (health.value as usize * (HEALTH_BAR_SEGMENTS - 2)) / health.max as usize
-                }
-            };
-            sprite_atlas.index = index;
+            if healthbar.is_shield {
+                // Only shown while the shield holds
+                *visibility = match health.shield_value() {
+                    0 => Visibility::Hidden,
+                    _ => Visibility::Inherited,
+                };
+                sprite_atlas.index = segment_index(health.shield_value(), health.shield_max());
+            } else {
+                sprite_atlas.index = segment_index(health.value, health.max);
+            }
         } else if healthbar.is_alive {
             healthbar.is_alive = false;
             commands.entity(healthbar_entity).despawn();
@@ -0,0 +1,106 @@
+use bevy::prelude::*;
+
+use crate::{
+    collision::spatial_query::{SpatialQuery, SpatialQueryFilter},
+    movement::{pause::ActionPauseState, velocity::Velocity},
+};
+
+use super::{
+    health::Dead,
+    projectile::Projectile,
+    teams::{Team, TeamMember},
+};
+
+/*
+Steers the projectile's velocity towards the nearest member of the target team
+*/
+#[derive(Component)]
+pub struct Homing {
+    // Radians per second
+    pub turn_rate: f32,
+    // Targets further away than this are ignored
+    pub acquisition_radius: f32,
+    pub target_team: Team,
+    target: Option<Entity>,
+}
+
+impl Homing {
+    pub fn new(turn_rate: f32, acquisition_radius: f32, target_team: Team) -> Homing {
+        Homing {
+            turn_rate,
+            acquisition_radius,
+            target_team,
+            target: None,
+        }
+    }
+}
+
+pub fn homing_update(
+    mut q_projectiles: Query<(&Projectile, &mut Homing, &mut Velocity, &Transform)>,
+    q_targets: Query<&Transform, (With<TeamMember>, Without<Dead>, Without<Projectile>)>,
+    spatial_query: SpatialQuery,
+    pause: Res<ActionPauseState>,
+    time: Res<Time>,
+) {
+    if pause.is_paused {
+        return;
+    }
+
+    for (projectile, mut homing, mut velocity, transform) in q_projectiles.iter_mut() {
+        if !projectile.is_alive {
+            continue;
+        }
+
+        let position = transform.translation.truncate();
+
+        // Look for a new target when the old one died, was hit or got out of reach
+        let target_position = homing
+            .target
+            .filter(|target| !projectile.entities_hit.contains(target))
+            .and_then(|target| q_targets.get(target).ok())
+            .map(|target_transform| target_transform.translation.truncate())
+            .filter(|target_position| {
+                target_position.distance(position) <= homing.acquisition_radius
+            });
+
+        let target_position = match target_position {
+            Some(target_position) => target_position,
+            None => {
+                let filter = SpatialQueryFilter {
+                    excluded: projectile.entities_hit.clone(),
+                    ..default()
+                }
+                .with_team(homing.target_team);
+
+                let target = spatial_query
+                    .nearest(position, 1, homing.acquisition_radius, &filter)
+                    .first()
+                    .and_then(|(entity, _)| {
+                        q_targets
+                            .get(*entity)
+                            .ok()
+                            .map(|target_transform| (*entity, target_transform))
+                    });
+
+                homing.target = target.map(|(entity, _)| entity);
+
+                match target {
+                    Some((_, target_transform)) => target_transform.translation.truncate(),
+                    None => continue, // Keep flying straight
+                }
+            }
+        };
+
+        let desired = target_position - position;
+        if desired == Vec2::ZERO || velocity.vec == Vec2::ZERO {
+            continue;
+        }
+
+        let max_turn = homing.turn_rate * time.delta_seconds();
+        let angle = velocity
+            .vec
+            .angle_between(desired)
+            .clamp(-max_turn, max_turn);
+        velocity.vec = Vec2::from_angle(angle).rotate(velocity.vec);
+    }
+}
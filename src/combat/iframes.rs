@@ -0,0 +1,122 @@
+use bevy::prelude::*;
+
+use crate::movement::pause::ActionPauseState;
+
+use super::health::{Health, TookDamageEvent};
+
+// How often blinking entities toggle between visible and faded
+const BLINK_INTERVAL: f32 = 0.1;
+const BLINK_ALPHA: f32 = 0.3;
+
+/*
+Invulnerability frames, the entity can't take damage while they are active.
+They are granted every time the entity takes damage that isn't a tick, or through `GrantIFramesEvent`,
+and the entity's sprite flashes while they are active
+*/
+#[derive(Component)]
+pub struct IFrames {
+    // Granted on every hit, 0 to only grant them through events
+    pub on_hit_duration: f32,
+    timer: Timer,
+    blink: Timer,
+}
+
+impl IFrames {
+    pub fn new(on_hit_duration: f32) -> IFrames {
+        let mut timer = Timer::from_seconds(0., TimerMode::Once);
+        timer.tick(default());
+
+        IFrames {
+            on_hit_duration,
+            timer,
+            blink: Timer::from_seconds(BLINK_INTERVAL, TimerMode::Repeating),
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.timer.finished()
+    }
+
+    // Keeps whichever invulnerability lasts longer
+    pub fn grant(&mut self, duration: f32) {
+        if self.timer.remaining_secs() >= duration {
+            return;
+        }
+
+        self.timer = Timer::from_seconds(duration, TimerMode::Once);
+        self.blink.reset();
+    }
+
+    pub fn clear(&mut self) {
+        let duration = self.timer.duration();
+        self.timer.set_elapsed(duration);
+        self.timer.tick(default());
+    }
+}
+
+// Lets abilities like a dash make an entity invulnerable for a while
+#[derive(Event)]
+pub struct GrantIFramesEvent {
+    pub entity: Entity,
+    pub duration: f32,
+}
+
+pub fn iframes_update(
+    mut q_iframes: Query<(&mut IFrames, &mut Health)>,
+    mut ev_dmg: EventReader<TookDamageEvent>,
+    mut ev_grant: EventReader<GrantIFramesEvent>,
+    pause: Res<ActionPauseState>,
+    time: Res<Time>,
+) {
+    if !pause.is_paused {
+        for (mut iframes, _) in q_iframes.iter_mut() {
+            iframes.timer.tick(time.delta());
+        }
+    }
+
+    for took_dmg in ev_dmg.iter() {
+        if took_dmg.damage.is_tick {
+            continue;
+        }
+
+        if let Ok((mut iframes, _)) = q_iframes.get_mut(took_dmg.entity) {
+            let duration = iframes.on_hit_duration;
+            iframes.grant(duration);
+        }
+    }
+
+    for grant in ev_grant.iter() {
+        if let Ok((mut iframes, _)) = q_iframes.get_mut(grant.entity) {
+            iframes.grant(grant.duration);
+        }
+    }
+
+    for (iframes, mut health) in q_iframes.iter_mut() {
+        if health.is_invincible != iframes.is_active() {
+            health.is_invincible = iframes.is_active();
+        }
+    }
+}
+
+pub fn iframes_blink(
+    mut q_iframes: Query<(&mut IFrames, &mut TextureAtlasSprite)>,
+    time: Res<Time>,
+) {
+    for (mut iframes, mut sprite) in q_iframes.iter_mut() {
+        if !iframes.is_active() {
+            if sprite.color.a() != 1. {
+                sprite.color.set_a(1.);
+            }
+            continue;
+        }
+
+        iframes.blink.tick(time.delta());
+        if iframes.blink.just_finished() {
+            let alpha = match sprite.color.a() == 1. {
+                true => BLINK_ALPHA,
+                false => 1.,
+            };
+            sprite.color.set_a(alpha);
+        }
+    }
+}
@@ -1,24 +1,78 @@
 use bevy::prelude::*;
 
-use crate::movement::velocity::Velocity;
+use crate::{
+    collision::solid::Solid,
+    movement::{pause::ActionPauseState, velocity::Velocity},
+};
 
-use super::projectile::ProjectileHitEvent;
+use super::health::{Dead, TookDamageEvent};
+
+// Knockback this strong once divided by the mass interrupts whatever the victim was doing
+const STAGGER_THRESHOLD: f32 = 40.;
+const STAGGER_DURATION: f32 = 0.4;
 
 #[derive(Component)]
 pub struct Knockback {
     pub force: f32,
 }
 
+// The AI doesn't act while staggered, so the knockback carries it
+#[derive(Component)]
+pub struct Staggered(Timer);
+
+// Pushes everyone who took damage by the damage's knockback,
+// solid bodies are pushed less the heavier they are
 pub fn knockback_update(
-    q_knock: Query<(&Knockback, &Velocity)>,
-    mut q_hit: Query<&mut Velocity, Without<Knockback>>,
-    mut ev_hits: EventReader<ProjectileHitEvent>,
+    mut q_hit: Query<(&mut Velocity, Option<&Solid>, Option<&mut Staggered>), Without<Dead>>,
+    mut ev_dmg: EventReader<TookDamageEvent>,
+    mut commands: Commands,
 ) {
-    for hit in ev_hits.iter() {
-        if let Ok((knock, projectile_velocity)) = q_knock.get(hit.projectile) {
-            if let Ok(mut hit_velocity) = q_hit.get_mut(hit.victim) {
-                hit_velocity.vec += projectile_velocity.vec.normalize() * knock.force;
+    for took_dmg in ev_dmg.iter() {
+        if took_dmg.damage.knockback == Vec2::ZERO {
+            continue;
+        }
+
+        if let Ok((mut hit_velocity, solid, staggered)) = q_hit.get_mut(took_dmg.entity) {
+            let impulse = match solid {
+                Some(solid) => took_dmg.damage.knockback / solid.mass,
+                None => took_dmg.damage.knockback,
+            };
+            hit_velocity.vec += impulse;
+
+            if impulse.length() < STAGGER_THRESHOLD {
+                continue;
+            }
+
+            match staggered {
+                Some(mut staggered) => staggered.0.reset(),
+                None => {
+                    commands
+                        .entity(took_dmg.entity)
+                        .insert(Staggered(Timer::from_seconds(
+                            STAGGER_DURATION,
+                            TimerMode::Once,
+                        )));
+                }
             }
         }
     }
 }
+
+pub fn stagger_update(
+    mut q_staggered: Query<(Entity, &mut Staggered)>,
+    pause: Res<ActionPauseState>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    if pause.is_paused {
+        return;
+    }
+
+    for (entity, mut staggered) in q_staggered.iter_mut() {
+        staggered.0.tick(time.delta());
+
+        if staggered.0.finished() {
+            commands.entity(entity).remove::<Staggered>();
+        }
+    }
+}
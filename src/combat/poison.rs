@@ -0,0 +1,41 @@
+use bevy::prelude::*;
+
+use crate::{
+    enemies::enemy::Enemy,
+    player::{ability::Ability, Player},
+};
+
+use super::{
+    projectile::ProjectileHitEvent,
+    status_effect::{ApplyStatusEffectEvent, StatusEffect, StatusEffectKind},
+};
+
+const POISON_DAMAGE: f32 = 1.;
+const POISON_DURATION: f32 = 3.;
+
+// Enemies hit while the player has venomous bullets get poisoned, every hit adds a stack
+pub fn venomous_bullets(
+    q_enemies: Query<(), With<Enemy>>,
+    q_player: Query<(Entity, &Player)>,
+    mut projectile_hit: EventReader<ProjectileHitEvent>,
+    mut apply_ev: EventWriter<ApplyStatusEffectEvent>,
+) {
+    let (player_entity, player) = q_player.single();
+
+    if !player.abilities.contains(&Ability::VenomousBullets) {
+        return;
+    }
+
+    for proj_hit in projectile_hit.iter() {
+        if !q_enemies.contains(proj_hit.victim) {
+            continue;
+        }
+
+        apply_ev.send(ApplyStatusEffectEvent {
+            target: proj_hit.victim,
+            effect: StatusEffect::new(StatusEffectKind::Poison, POISON_DAMAGE, POISON_DURATION)
+                .with_source(player_entity)
+                .with_ability(Ability::VenomousBullets),
+        });
+    }
+}
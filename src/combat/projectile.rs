@@ -1,11 +1,19 @@
-use bevy::prelude::*;
+use bevy::{prelude::*, utils::HashMap, window::PrimaryWindow};
 
-use crate::collision::collider::CollisionStartEvent;
-use crate::combat::health::HealthType;
+use crate::collision::{
+    collider::{Collider, CollisionStartEvent},
+    layers::CollisionLayer,
+};
+use crate::movement::{edge_teleport::EDGE_GRACE, pause::ActionPauseState, velocity::Velocity};
 
+use super::bounce::Bounces;
+use super::damage::{DamageInfo, Resistances, CRIT_KNOCKBACK_MULTIPLIER};
 use super::health::{Dead, Health, TookDamageEvent};
+use super::knockback::Knockback;
+use super::status_effect::StatusEffects;
 use super::teams::{Team, TeamMember};
 
+#[derive(Clone, Copy)]
 pub enum DamageTarget {
     All,
     Team(Team),
@@ -20,7 +28,8 @@ pub enum PiercingMode {
 #[derive(Component)]
 pub struct Projectile {
     pub damage_target: DamageTarget,
-    pub dmg: HealthType,
+    // Knockback is added on hit, from the projectile's `Knockback` and direction
+    pub damage: DamageInfo,
     pub piercing_mode: PiercingMode,
     pub entities_hit: Vec<Entity>,
     pub is_alive: bool,
@@ -30,42 +39,179 @@ pub struct Projectile {
 pub struct ProjectileHitEvent {
     pub projectile: Entity,
     pub victim: Entity,
+    // Set when a fast projectile was caught between frames, see `Collision::time_of_impact`
+    pub time_of_impact: Option<f32>,
+    // The projectile used up a bounce instead of being destroyed
+    pub bounced: bool,
+}
+
+// Despawns the projectile once the timer finishes
+#[derive(Component)]
+pub struct Lifetime(pub Timer);
+
+impl Lifetime {
+    pub fn new(seconds: f32) -> Lifetime {
+        Lifetime(Timer::from_seconds(seconds, TimerMode::Once))
+    }
+}
+
+// Despawns the projectile once it is this far away from where it was first seen
+#[derive(Component)]
+pub struct MaxRange {
+    pub range: f32,
+    origin: Option<Vec2>,
+}
+
+impl MaxRange {
+    pub fn new(range: f32) -> MaxRange {
+        MaxRange {
+            range,
+            origin: None,
+        }
+    }
+}
+
+// Despawns the projectile once it leaves the arena
+#[derive(Component)]
+pub struct EdgeDespawns;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ExpiryReason {
+    Lifetime,
+    MaxRange,
+    LeftArena,
+}
+
+/*
+Sent when a projectile runs out of lifetime or range or leaves the arena,
+piercing projectiles that already hit something send it as well
+*/
+#[derive(Event)]
+pub struct ProjectileExpiredEvent {
+    pub projectile: Entity,
+    pub position: Vec2,
+    pub reason: ExpiryReason,
 }
 
 pub fn projectile_collision_check(
-    mut q_projectiles: Query<&mut Projectile, Without<Dead>>,
-    mut q_hittable: Query<(&mut Health, &TeamMember)>,
+    mut q_projectiles: Query<
+        (
+            &mut Projectile,
+            Option<&Knockback>,
+            Option<&Velocity>,
+            Option<&mut Bounces>,
+        ),
+        Without<Dead>,
+    >,
+    mut q_hittable: Query<
+        (
+            &mut Health,
+            &TeamMember,
+            Option<&Resistances>,
+            Option<&StatusEffects>,
+        ),
+        Without<Dead>,
+    >,
+    // Obstacles that can't be destroyed
+    q_walls: Query<&Collider, (Without<Health>, Without<Projectile>)>,
     mut ev_collision: EventReader<CollisionStartEvent>,
     mut ev_hit: EventWriter<ProjectileHitEvent>,
     mut ev_dmg: EventWriter<TookDamageEvent>,
     mut commands: Commands,
 ) {
-    for ev_is_colliding in ev_collision.iter() {
-        if let (Ok(bullet), Ok((health, member))) = (
+    let collisions: Vec<&CollisionStartEvent> = ev_collision.iter().collect();
+
+    // Fast projectiles sweep through everything on their path,
+    // but can't hit what's behind the first wall they reach
+    let mut wall_hits: HashMap<Entity, f32> = HashMap::new();
+    for ev in collisions.iter() {
+        let (entity_a, entity_b) = (ev.collision.entity_a, ev.collision.entity_b);
+
+        for (projectile, wall) in [(entity_a, entity_b), (entity_b, entity_a)] {
+            let is_wall = q_walls
+                .get(wall)
+                .is_ok_and(|collider| collider.layers().contains(CollisionLayer::Obstacle));
+
+            if is_wall && q_projectiles.contains(projectile) {
+                let time_of_impact = ev.collision.time_of_impact.unwrap_or(1.);
+                let earliest = wall_hits.entry(projectile).or_insert(time_of_impact);
+                *earliest = earliest.min(time_of_impact);
+            }
+        }
+    }
+
+    let is_behind_wall = |projectile: Entity, time_of_impact: Option<f32>| {
+        wall_hits
+            .get(&projectile)
+            .is_some_and(|wall| time_of_impact.unwrap_or(1.) > *wall)
+    };
+
+    for ev_is_colliding in collisions {
+        if is_behind_wall(
+            ev_is_colliding.collision.entity_a,
+            ev_is_colliding.collision.time_of_impact,
+        ) || is_behind_wall(
+            ev_is_colliding.collision.entity_b,
+            ev_is_colliding.collision.time_of_impact,
+        ) {
+            continue;
+        }
+
+        if let (
+            Ok((bullet, knockback, velocity, bounces)),
+            Ok((health, member, resistances, status_effects)),
+        ) = (
             q_projectiles.get_mut(ev_is_colliding.collision.entity_a),
             q_hittable.get_mut(ev_is_colliding.collision.entity_b),
         ) {
+            let knockback = match (knockback, velocity) {
+                (Some(knockback), Some(velocity)) => {
+                    velocity.vec.normalize_or_zero() * knockback.force
+                }
+                _ => Vec2::ZERO,
+            };
+
             handle_projectile_collision(
                 ev_is_colliding.collision.entity_a,
                 bullet,
+                knockback,
+                bounces,
                 ev_is_colliding.collision.entity_b,
                 health,
                 member.team,
+                resistances,
+                status_effects,
+                ev_is_colliding.collision.time_of_impact,
                 &mut ev_hit,
                 &mut ev_dmg,
                 &mut commands,
             );
         }
-        if let (Ok(bullet), Ok((health, member))) = (
+        if let (
+            Ok((bullet, knockback, velocity, bounces)),
+            Ok((health, member, resistances, status_effects)),
+        ) = (
             q_projectiles.get_mut(ev_is_colliding.collision.entity_b),
             q_hittable.get_mut(ev_is_colliding.collision.entity_a),
         ) {
+            let knockback = match (knockback, velocity) {
+                (Some(knockback), Some(velocity)) => {
+                    velocity.vec.normalize_or_zero() * knockback.force
+                }
+                _ => Vec2::ZERO,
+            };
+
             handle_projectile_collision(
                 ev_is_colliding.collision.entity_b,
                 bullet,
+                knockback,
+                bounces,
                 ev_is_colliding.collision.entity_a,
                 health,
                 member.team,
+                resistances,
+                status_effects,
+                ev_is_colliding.collision.time_of_impact,
                 &mut ev_hit,
                 &mut ev_dmg,
                 &mut commands,
@@ -77,9 +223,14 @@ pub fn projectile_collision_check(
 fn handle_projectile_collision(
     projectile_entity: Entity,
     mut projectile: Mut<Projectile>,
+    knockback: Vec2,
+    bounces: Option<Mut<Bounces>>,
     hit_entity: Entity,
     mut health: Mut<Health>,
     hit_team: Team,
+    resistances: Option<&Resistances>,
+    status_effects: Option<&StatusEffects>,
+    time_of_impact: Option<f32>,
     ev_hit: &mut EventWriter<ProjectileHitEvent>,
     ev_dmg: &mut EventWriter<TookDamageEvent>,
     commands: &mut Commands,
@@ -107,22 +258,106 @@ fn handle_projectile_collision(
         }
     }
 
-    ev_hit.send(ProjectileHitEvent {
-        projectile: projectile_entity,
-        victim: hit_entity,
-    });
-
     projectile.entities_hit.push(hit_entity);
-    health.take_damage(hit_entity, ev_dmg, projectile.dmg);
+    let knockback = match projectile.damage.is_crit {
+        true => knockback * CRIT_KNOCKBACK_MULTIPLIER,
+        false => knockback,
+    };
+
+    let damage = projectile.damage.with_knockback(knockback);
+    health.take_damage(hit_entity, ev_dmg, damage, resistances, status_effects);
 
-    let is_dead = match projectile.piercing_mode {
+    let mut is_dead = match projectile.piercing_mode {
         PiercingMode::None => true,
         PiercingMode::Count(count) => projectile.entities_hit.len() >= count,
         PiercingMode::All => false,
     };
 
+    // Bouncing projectiles survive until they run out of bounces
+    let bounced = is_dead && bounces.is_some_and(|mut bounces| bounces.consume());
+    if bounced {
+        is_dead = false;
+    }
+
+    ev_hit.send(ProjectileHitEvent {
+        projectile: projectile_entity,
+        victim: hit_entity,
+        time_of_impact,
+        bounced,
+    });
+
     if is_dead {
         commands.entity(projectile_entity).despawn();
         projectile.is_alive = false;
     }
 }
+
+pub fn projectile_expiry(
+    mut q_projectiles: Query<
+        (
+            Entity,
+            &mut Projectile,
+            &Transform,
+            Option<&mut Lifetime>,
+            Option<&mut MaxRange>,
+            Option<&EdgeDespawns>,
+        ),
+        Without<Dead>,
+    >,
+    q_windows: Query<&Window, With<PrimaryWindow>>,
+    mut ev_expired: EventWriter<ProjectileExpiredEvent>,
+    pause: Res<ActionPauseState>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    if pause.is_paused {
+        return;
+    }
+
+    let window = q_windows.single();
+    let half_arena = Vec2::new(window.width(), window.height()) / 2. + EDGE_GRACE;
+
+    for (entity, mut projectile, transform, lifetime, max_range, edge_despawns) in
+        q_projectiles.iter_mut()
+    {
+        if !projectile.is_alive {
+            continue; // Already hit something this frame
+        }
+
+        let position = transform.translation.truncate();
+
+        let mut reason = None;
+
+        if let Some(mut lifetime) = lifetime {
+            if lifetime.0.tick(time.delta()).finished() {
+                reason = Some(ExpiryReason::Lifetime);
+            }
+        }
+
+        if let Some(mut max_range) = max_range {
+            let origin = *max_range.origin.get_or_insert(position);
+            if origin.distance(position) > max_range.range {
+                reason = Some(ExpiryReason::MaxRange);
+            }
+        }
+
+        if edge_despawns.is_some()
+            && (position.x.abs() > half_arena.x || position.y.abs() > half_arena.y)
+        {
+            reason = Some(ExpiryReason::LeftArena);
+        }
+
+        let Some(reason) = reason else {
+            continue;
+        };
+
+        commands.entity(entity).despawn();
+        projectile.is_alive = false;
+
+        ev_expired.send(ProjectileExpiredEvent {
+            projectile: entity,
+            position,
+            reason,
+        });
+    }
+}
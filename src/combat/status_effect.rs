@@ -0,0 +1,380 @@
+use bevy::prelude::*;
+
+use crate::{movement::pause::ActionPauseState, player::ability::Ability};
+
+use super::{
+    damage::{DamageInfo, DamageType, Resistances},
+    health::{Dead, DeathEvent, Health, HealthType, TookDamageEvent},
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum StatusEffectKind {
+    // Fire damage over time
+    Burn,
+    // Poison damage over time, stacks
+    Poison,
+    // Reduces movement speed by the potency
+    Slow,
+    // Can't move or act
+    Freeze,
+    // Can't move or act
+    Stun,
+    // Takes more damage from every source by the potency per stack
+    Vulnerability,
+}
+
+// What happens when an effect is applied to an entity that already has it
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Stacking {
+    // Only the duration restarts
+    Refresh,
+    // Adds a stack up to the max and restarts the duration
+    Intensity { max_stacks: u32 },
+}
+
+impl StatusEffectKind {
+    pub fn stacking(&self) -> Stacking {
+        match self {
+            StatusEffectKind::Poison => Stacking::Intensity { max_stacks: 5 },
+            StatusEffectKind::Vulnerability => Stacking::Intensity { max_stacks: 3 },
+            _ => Stacking::Refresh,
+        }
+    }
+
+    // Seconds between two damage ticks, None for effects that don't deal damage
+    pub fn tick_rate(&self) -> Option<f32> {
+        match self {
+            StatusEffectKind::Burn => Some(1.),
+            StatusEffectKind::Poison => Some(0.5),
+            _ => None,
+        }
+    }
+
+    pub fn damage_type(&self) -> Option<DamageType> {
+        match self {
+            StatusEffectKind::Burn => Some(DamageType::Fire),
+            StatusEffectKind::Poison => Some(DamageType::Poison),
+            _ => None,
+        }
+    }
+
+    // Burning has its own animation instead of a tint
+    fn tint(&self) -> Option<Color> {
+        match self {
+            StatusEffectKind::Burn => None,
+            StatusEffectKind::Poison => Some(Color::rgb(0.6, 1., 0.5)),
+            StatusEffectKind::Slow => Some(Color::rgb(0.7, 0.8, 1.)),
+            StatusEffectKind::Freeze => Some(Color::rgb(0.5, 0.8, 1.)),
+            StatusEffectKind::Stun => Some(Color::rgb(1., 1., 0.5)),
+            StatusEffectKind::Vulnerability => Some(Color::rgb(1., 0.6, 0.6)),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct StatusEffect {
+    pub kind: StatusEffectKind,
+    // Damage per tick and stack, or the strength of the effect
+    pub potency: f32,
+    pub stacks: u32,
+    pub source: Option<Entity>,
+    pub ability: Option<Ability>,
+    duration: Timer,
+    tick: Option<Timer>,
+}
+
+impl StatusEffect {
+    pub fn new(kind: StatusEffectKind, potency: f32, duration: f32) -> StatusEffect {
+        StatusEffect {
+            kind,
+            potency,
+            stacks: 1,
+            source: None,
+            ability: None,
+            duration: Timer::from_seconds(duration, TimerMode::Once),
+            tick: kind
+                .tick_rate()
+                .map(|rate| Timer::from_seconds(rate, TimerMode::Repeating)),
+        }
+    }
+
+    pub fn with_source(mut self, source: Entity) -> StatusEffect {
+        self.source = Some(source);
+        self
+    }
+
+    pub fn with_ability(mut self, ability: Ability) -> StatusEffect {
+        self.ability = Some(ability);
+        self
+    }
+
+    fn damage(&self) -> Option<DamageInfo> {
+        let damage_type = self.kind.damage_type()?;
+        let amount = (self.potency * self.stacks as f32).round() as HealthType;
+        let mut damage = DamageInfo::new(amount, damage_type).as_tick();
+        damage.source = self.source;
+        damage.ability = self.ability;
+
+        Some(damage)
+    }
+}
+
+/*
+The status effects currently on an entity, only entities with this component can receive effects
+*/
+#[derive(Component, Default)]
+pub struct StatusEffects {
+    effects: Vec<StatusEffect>,
+}
+
+impl StatusEffects {
+    pub fn get(&self, kind: StatusEffectKind) -> Option<&StatusEffect> {
+        self.effects.iter().find(|effect| effect.kind == kind)
+    }
+
+    pub fn has(&self, kind: StatusEffectKind) -> bool {
+        self.get(kind).is_some()
+    }
+
+    pub fn clear(&mut self) {
+        self.effects.clear();
+    }
+
+    // Whether the entity can't move or act at all
+    pub fn is_incapacitated(&self) -> bool {
+        self.has(StatusEffectKind::Freeze) || self.has(StatusEffectKind::Stun)
+    }
+
+    pub fn speed_multiplier(&self) -> f32 {
+        if self.is_incapacitated() {
+            return 0.;
+        }
+
+        match self.get(StatusEffectKind::Slow) {
+            Some(slow) => (1. - slow.potency).max(0.),
+            None => 1.,
+        }
+    }
+
+    // Scales damage taken by the vulnerability stacks
+    pub fn amplify(&self, mut damage: DamageInfo) -> DamageInfo {
+        if let Some(vulnerability) = self.get(StatusEffectKind::Vulnerability) {
+            let multiplier = 1. + vulnerability.potency * vulnerability.stacks as f32;
+            damage.amount = (damage.amount as f32 * multiplier).round() as HealthType;
+        }
+
+        damage
+    }
+
+    // Returns whether the effect is new
+    fn apply(&mut self, effect: StatusEffect) -> bool {
+        let Some(existing) = self.effects.iter_mut().find(|e| e.kind == effect.kind) else {
+            self.effects.push(effect);
+            return true;
+        };
+
+        if let Stacking::Intensity { max_stacks } = effect.kind.stacking() {
+            existing.stacks = (existing.stacks + 1).min(max_stacks);
+        }
+
+        // Keep the stronger effect and restart the duration
+        existing.potency = existing.potency.max(effect.potency);
+        existing.source = effect.source;
+        existing.ability = effect.ability;
+        existing.duration = effect.duration;
+
+        false
+    }
+
+    fn tint(&self) -> Color {
+        self.effects
+            .iter()
+            .find_map(|effect| effect.kind.tint())
+            .unwrap_or(Color::WHITE)
+    }
+}
+
+#[derive(Event)]
+pub struct ApplyStatusEffectEvent {
+    pub target: Entity,
+    pub effect: StatusEffect,
+}
+
+#[derive(Event)]
+pub struct StatusEffectAppliedEvent {
+    pub entity: Entity,
+    pub kind: StatusEffectKind,
+    // False if the effect was only refreshed or stacked
+    pub is_new: bool,
+}
+
+#[derive(Event)]
+pub struct StatusEffectExpiredEvent {
+    pub entity: Entity,
+    pub kind: StatusEffectKind,
+}
+
+pub fn apply_status_effects(
+    mut q_effects: Query<&mut StatusEffects, Without<Dead>>,
+    mut apply_ev: EventReader<ApplyStatusEffectEvent>,
+    mut applied_ev: EventWriter<StatusEffectAppliedEvent>,
+) {
+    for ev in apply_ev.iter() {
+        let Ok(mut effects) = q_effects.get_mut(ev.target) else {
+            continue; // Immune to status effects or dead
+        };
+
+        let is_new = effects.apply(ev.effect.clone());
+
+        applied_ev.send(StatusEffectAppliedEvent {
+            entity: ev.target,
+            kind: ev.effect.kind,
+            is_new,
+        });
+    }
+}
+
+pub fn status_effects_update(
+    mut q_effects: Query<(
+        Entity,
+        &mut StatusEffects,
+        Option<&mut Health>,
+        Option<&Resistances>,
+    )>,
+    mut took_damage_ev: EventWriter<TookDamageEvent>,
+    mut expired_ev: EventWriter<StatusEffectExpiredEvent>,
+    pause: Res<ActionPauseState>,
+    time: Res<Time>,
+) {
+    if pause.is_paused {
+        return;
+    }
+
+    for (entity, mut effects, mut health, resistances) in q_effects.iter_mut() {
+        if effects.effects.is_empty() {
+            continue;
+        }
+
+        let mut damages = vec![];
+
+        for effect in effects.effects.iter_mut() {
+            effect.duration.tick(time.delta());
+
+            let Some(tick) = effect.tick.as_mut() else {
+                continue;
+            };
+
+            tick.tick(time.delta());
+            for _ in 0..tick.times_finished_this_tick() {
+                damages.extend(effect.damage());
+            }
+        }
+
+        if let Some(health) = health.as_mut() {
+            for damage in damages {
+                health.take_damage(
+                    entity,
+                    &mut took_damage_ev,
+                    damage,
+                    resistances,
+                    Some(&effects),
+                );
+            }
+        }
+
+        effects.effects.retain(|effect| {
+            if effect.duration.finished() {
+                expired_ev.send(StatusEffectExpiredEvent {
+                    entity,
+                    kind: effect.kind,
+                });
+            }
+
+            !effect.duration.finished()
+        });
+    }
+}
+
+// The dead lose their effects right away, so their visuals go with them
+pub fn expire_status_effects_on_death(
+    mut q_effects: Query<&mut StatusEffects>,
+    mut death_ev: EventReader<DeathEvent>,
+    mut expired_ev: EventWriter<StatusEffectExpiredEvent>,
+) {
+    for death in death_ev.iter() {
+        let Ok(mut effects) = q_effects.get_mut(death.entity) else {
+            continue;
+        };
+
+        for effect in effects.effects.drain(..) {
+            expired_ev.send(StatusEffectExpiredEvent {
+                entity: death.entity,
+                kind: effect.kind,
+            });
+        }
+    }
+}
+
+pub fn status_effect_tints(
+    mut q_sprites: Query<(&StatusEffects, &mut TextureAtlasSprite), Changed<StatusEffects>>,
+) {
+    for (effects, mut sprite) in q_sprites.iter_mut() {
+        // Keeps the alpha, which blinking and fading use
+        let alpha = sprite.color.a();
+        sprite.color = effects.tint().with_a(alpha);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refreshing_keeps_a_single_stack() {
+        let mut effects = StatusEffects::default();
+
+        assert!(effects.apply(StatusEffect::new(StatusEffectKind::Burn, 1., 4.)));
+        assert!(!effects.apply(StatusEffect::new(StatusEffectKind::Burn, 2., 4.)));
+
+        let burn = effects.get(StatusEffectKind::Burn).unwrap();
+        assert_eq!(burn.stacks, 1);
+        assert_eq!(burn.potency, 2.);
+    }
+
+    #[test]
+    fn intensity_stacks_up_to_the_max() {
+        let mut effects = StatusEffects::default();
+        let Stacking::Intensity { max_stacks } = StatusEffectKind::Poison.stacking() else {
+            panic!("poison should stack in intensity");
+        };
+
+        for stacks in 1..=max_stacks + 2 {
+            effects.apply(StatusEffect::new(StatusEffectKind::Poison, 1., 3.));
+
+            let poison = effects.get(StatusEffectKind::Poison).unwrap();
+            assert_eq!(poison.stacks, stacks.min(max_stacks));
+        }
+
+        let damage = effects
+            .get(StatusEffectKind::Poison)
+            .and_then(StatusEffect::damage)
+            .unwrap();
+        assert_eq!(damage.amount, max_stacks as HealthType);
+    }
+
+    #[test]
+    fn applying_again_restarts_the_duration() {
+        let mut effects = StatusEffects::default();
+        effects.apply(StatusEffect::new(StatusEffectKind::Vulnerability, 0.5, 2.));
+        effects.effects[0]
+            .duration
+            .tick(std::time::Duration::from_secs_f32(1.5));
+
+        effects.apply(StatusEffect::new(StatusEffectKind::Vulnerability, 0.5, 2.));
+
+        let vulnerability = effects.get(StatusEffectKind::Vulnerability).unwrap();
+        assert_eq!(vulnerability.duration.elapsed_secs(), 0.);
+        assert_eq!(vulnerability.stacks, 2);
+        assert_eq!(effects.amplify(DamageInfo::physical(10)).amount, 20);
+    }
+}
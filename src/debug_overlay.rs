@@ -0,0 +1,101 @@
+use bevy::prelude::*;
+
+use crate::{
+    collision::collider::{Collider, ColliderPose, SpatialGrid},
+    enemies::ai::{MoveAndShootAI, MoveAndShootAIState},
+    experience::experience::Experience,
+    GameState,
+};
+
+const TOGGLE_KEY: KeyCode = KeyCode::F3;
+
+/*
+Draws hitboxes, the spatial grid and AI ranges on top of the game, only part of debug builds
+*/
+pub struct DebugOverlayPlugin;
+
+impl Plugin for DebugOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DebugOverlay>()
+            .add_systems(Update, toggle_debug_overlay)
+            .add_systems(
+                Update,
+                (
+                    draw_spatial_grid,
+                    draw_colliders,
+                    draw_move_and_shoot_ai,
+                    draw_pick_distance,
+                )
+                    .run_if(in_state(GameState::Playing))
+                    .run_if(|overlay: Res<DebugOverlay>| overlay.is_enabled),
+            );
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct DebugOverlay {
+    pub is_enabled: bool,
+}
+
+fn toggle_debug_overlay(keyboard_input: Res<Input<KeyCode>>, mut overlay: ResMut<DebugOverlay>) {
+    if keyboard_input.just_pressed(TOGGLE_KEY) {
+        overlay.is_enabled = !overlay.is_enabled;
+    }
+}
+
+fn draw_spatial_grid(grid: Res<SpatialGrid>, mut gizmos: Gizmos) {
+    let cell_size = grid.cell_size();
+
+    for (x, y) in grid.occupied_cells() {
+        let center = (Vec2::new(x as f32, y as f32) + 0.5) * cell_size;
+        gizmos.rect_2d(center, 0., Vec2::splat(cell_size), Color::DARK_GRAY);
+    }
+}
+
+fn draw_colliders(q_colliders: Query<(&Collider, &Transform)>, mut gizmos: Gizmos) {
+    for (collider, transform) in q_colliders.iter() {
+        let core = collider.core(ColliderPose::from(transform));
+        let color = Color::LIME_GREEN;
+
+        if core.radius == 0. {
+            for (start, end) in core.edges() {
+                gizmos.line_2d(start, end, color);
+            }
+            continue;
+        }
+
+        // The outline of a grown core is its edges pushed out plus circles around the vertices
+        for vertex in core.vertices.iter() {
+            gizmos.circle_2d(*vertex, core.radius, color);
+        }
+
+        for (start, end) in core.edges() {
+            let offset = (end - start).perp().normalize_or_zero() * core.radius;
+
+            gizmos.line_2d(start + offset, end + offset, color);
+            gizmos.line_2d(start - offset, end - offset, color);
+        }
+    }
+}
+
+fn draw_move_and_shoot_ai(q_ai: Query<(&MoveAndShootAI, &Transform)>, mut gizmos: Gizmos) {
+    for (ai, transform) in q_ai.iter() {
+        let color = match ai.state() {
+            MoveAndShootAIState::Move => Color::WHITE,
+            MoveAndShootAIState::Slow => Color::YELLOW,
+            MoveAndShootAIState::Charge => Color::RED,
+        };
+
+        gizmos.circle_2d(transform.translation.truncate(), ai.shoot_distance, color);
+    }
+}
+
+fn draw_pick_distance(q_experience: Query<(&Experience, &Transform)>, mut gizmos: Gizmos) {
+    for (experience, transform) in q_experience.iter() {
+        gizmos.circle_2d(
+            transform.translation.truncate(),
+            experience.pick_distance,
+            Color::CYAN,
+        );
+    }
+}
@@ -1,11 +1,11 @@
 use bevy::{prelude::*, time::Stopwatch};
 
-use crate::{animation::AppAnimationSetup, GameState};
+use crate::{animation::AppAnimationSetup, combat::health::check_death, GameState};
 
 use self::{
     ai::{follow_player, move_and_shoot_ai, ChargeShootEvent, ShootEvent},
     beholder::{beholder_update, BeholderAnimation, BeholderProjectileAnimation},
-    enemy::{death_loop, initial_spawn, spread_enemies, EnemyDeathEvent},
+    enemy::{death_loop, fade_dead_enemies, initial_spawn, EnemyDeathEvent},
     imp::ImpAnimation,
     reaper::{reaper_blade_update, reaper_update, ReaperAnimation, ReaperBladeAnimation},
     spawning::{spawn_loop, spawn_spawn_rng, SpawnInfo},
@@ -29,8 +29,8 @@ impl Plugin for EnemiesPlugin {
                 (
                     follow_player,
                     move_and_shoot_ai,
-                    death_loop,
-                    spread_enemies,
+                    death_loop.after(check_death),
+                    fade_dead_enemies,
                     spawn_loop,
                     beholder_update,
                     reaper_update,
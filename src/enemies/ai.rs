@@ -3,6 +3,11 @@ use std::f32::consts::PI;
 use bevy::prelude::*;
 
 use crate::{
+    collision::{
+        layers::CollisionLayer,
+        spatial_query::{SpatialQuery, SpatialQueryFilter},
+    },
+    combat::{health::Dead, knockback::Staggered, status_effect::StatusEffects},
     movement::{pause::ActionPauseState, velocity::Velocity},
     player::Player,
     util::radians::Radian,
@@ -18,7 +23,15 @@ pub struct FollowPlayerAI {
 }
 
 pub fn follow_player(
-    mut q_enemies: Query<(&Transform, &FollowPlayerAI, &mut Velocity)>,
+    mut q_enemies: Query<
+        (
+            &Transform,
+            &FollowPlayerAI,
+            &mut Velocity,
+            Option<&Staggered>,
+        ),
+        Without<Dead>,
+    >,
     q_player: Query<&Transform, (With<Player>, Without<FollowPlayerAI>)>,
     pause: Res<ActionPauseState>,
 ) {
@@ -28,7 +41,11 @@ pub fn follow_player(
 
     let player_transform = q_player.single();
 
-    for (transform, ai, mut velocity) in q_enemies.iter_mut() {
+    for (transform, ai, mut velocity, staggered) in q_enemies.iter_mut() {
+        if staggered.is_some() {
+            continue; // Lets the knockback carry it
+        }
+
         let direction = player_transform.translation.truncate() - transform.translation.truncate();
         // obtain angle to target with respect to x-axis.
         let angle_to_target = Radian::from(direction.y.atan2(direction.x) - PI / 2.);
@@ -87,6 +104,10 @@ impl MoveAndShootAI {
             refresh_timer: Timer::from_seconds(refresh_time, TimerMode::Once),
         }
     }
+
+    pub fn state(&self) -> &MoveAndShootAIState {
+        &self.state
+    }
 }
 
 #[derive(Event)]
@@ -101,10 +122,21 @@ pub struct ShootEvent {
 }
 
 pub fn move_and_shoot_ai(
-    mut q_enemies: Query<(Entity, &Transform, &mut MoveAndShootAI, &mut Velocity)>,
+    mut q_enemies: Query<
+        (
+            Entity,
+            &Transform,
+            &mut MoveAndShootAI,
+            &mut Velocity,
+            Option<&StatusEffects>,
+            Option<&Staggered>,
+        ),
+        Without<Dead>,
+    >,
     q_player: Query<(Entity, &Transform), (With<Player>, Without<FollowPlayerAI>)>,
     mut charge_ev: EventWriter<ChargeShootEvent>,
     mut shoot_ev: EventWriter<ShootEvent>,
+    spatial_query: SpatialQuery,
     pause: Res<ActionPauseState>,
     time: Res<Time>,
 ) {
@@ -113,14 +145,30 @@ pub fn move_and_shoot_ai(
     }
 
     let (player_entity, player_transform) = q_player.single();
+    let sight_blockers = SpatialQueryFilter::default().with_layers(&[CollisionLayer::Obstacle]);
+
+    for (entity, transform, mut ai, mut velocity, status_effects, staggered) in q_enemies.iter_mut()
+    {
+        if status_effects.is_some_and(StatusEffects::is_incapacitated) {
+            continue; // Frozen in place, the charge continues once it wears off
+        }
+
+        if staggered.is_some() {
+            continue; // Same for heavy hits, but the knockback carries it
+        }
 
-    for (entity, transform, mut ai, mut velocity) in q_enemies.iter_mut() {
         ai.charge_timer.tick(time.delta());
         ai.refresh_timer.tick(time.delta());
 
         if ai.state == MoveAndShootAIState::Move
             && (player_transform.translation.distance(transform.translation) <= ai.shoot_distance)
             && ai.refresh_timer.finished()
+            && has_line_of_sight(
+                &spatial_query,
+                &sight_blockers,
+                transform.translation.truncate(),
+                player_transform.translation.truncate(),
+            )
         {
             ai.state = MoveAndShootAIState::Slow;
         }
@@ -165,3 +213,16 @@ pub fn move_and_shoot_ai(
         }
     }
 }
+
+fn has_line_of_sight(
+    spatial_query: &SpatialQuery,
+    blockers: &SpatialQueryFilter,
+    from: Vec2,
+    to: Vec2,
+) -> bool {
+    let direction = to - from;
+
+    spatial_query
+        .ray_cast(from, direction, direction.length(), blockers)
+        .is_none()
+}
@@ -10,11 +10,14 @@ use crate::{
         make_animation_bundle, Animation, AnimationStateChangeEvent, AnimationStateStorage,
     },
     audio::FXChannel,
-    collision::collider::Collider,
+    collision::{collider::Collider, layers::CollisionLayer, solid::Solid},
     combat::{
-        health::Health,
+        damage::{DamageInfo, DamageType, Resistances},
+        health::{Health, HealthType, Shield},
         healthbar::NeedsHealthBar,
-        projectile::{DamageTarget, PiercingMode, Projectile},
+        homing::Homing,
+        projectile::{DamageTarget, EdgeDespawns, Lifetime, PiercingMode, Projectile},
+        status_effect::StatusEffects,
         teams::{Team, TeamMember},
     },
     loading::{AudioAssets, TextureAssets},
@@ -28,6 +31,15 @@ use super::{
     enemy::{Enemy, EnemyType},
 };
 
+// Fireballs are slow, so they could drift around the arena for a long time
+const FIREBALL_LIFETIME: f32 = 20.;
+const HOMING_FIREBALL_TURN_RATE: f32 = 1.;
+const HOMING_FIREBALL_RADIUS: f32 = 400.;
+// The prince's shield has to be broken before it takes damage
+const PRINCE_SHIELD: HealthType = 50;
+const PRINCE_SHIELD_RECHARGE_DELAY: f32 = 4.;
+const PRINCE_SHIELD_RECHARGE_TIME: f32 = 2.;
+
 #[derive(PartialEq, Eq, Hash, Clone, Copy)]
 pub enum BeholderAnimation {
     Flying,
@@ -123,16 +135,18 @@ pub fn beholder_update(
 
             fx.play(audio.fireball.clone());
 
-            commands
-                .spawn(make_animation_bundle(
-                    BeholderProjectileAnimation::Flying,
-                    &beholder_projetile_animations,
-                    texture_atlas_handle.clone(),
-                    transform.translation,
-                    1.,
-                ))
+            let is_prince = q_beholder_prince.get(entity).is_ok();
+
+            let mut fireball = commands.spawn(make_animation_bundle(
+                BeholderProjectileAnimation::Flying,
+                &beholder_projetile_animations,
+                texture_atlas_handle.clone(),
+                transform.translation,
+                1.,
+            ));
+            fireball
                 .insert(Projectile {
-                    dmg: 1,
+                    damage: DamageInfo::new(1, DamageType::Fire).with_source(entity),
                     damage_target: DamageTarget::Team(Team::Player),
                     piercing_mode: PiercingMode::None,
                     entities_hit: vec![],
@@ -141,9 +155,18 @@ pub fn beholder_update(
                 .insert(Velocity {
                     vec: direction_vec * 40.,
                 })
-                .insert(Collider::new_circle(15., transform.translation.truncate()));
+                .insert(Collider::new_circle(15.).with_layer(CollisionLayer::EnemyProjectile))
+                .insert(Lifetime::new(FIREBALL_LIFETIME))
+                .insert(EdgeDespawns);
+
+            if is_prince {
+                // The prince's middle fireball follows the player
+                fireball.insert(Homing::new(
+                    HOMING_FIREBALL_TURN_RATE,
+                    HOMING_FIREBALL_RADIUS,
+                    Team::Player,
+                ));
 
-            if let Ok(_) = q_beholder_prince.get(entity) {
                 commands
                     .spawn(make_animation_bundle(
                         BeholderProjectileAnimation::Flying,
@@ -153,7 +176,7 @@ pub fn beholder_update(
                         1.,
                     ))
                     .insert(Projectile {
-                        dmg: 1,
+                        damage: DamageInfo::new(1, DamageType::Fire).with_source(entity),
                         damage_target: DamageTarget::Team(Team::Player),
                         piercing_mode: PiercingMode::None,
                         entities_hit: vec![],
@@ -162,7 +185,9 @@ pub fn beholder_update(
                     .insert(Velocity {
                         vec: (angle_to_target + Radian::from_degrees(10.)).unit_vector() * 40.,
                     })
-                    .insert(Collider::new_circle(15., transform.translation.truncate()));
+                    .insert(Collider::new_circle(15.).with_layer(CollisionLayer::EnemyProjectile))
+                    .insert(Lifetime::new(FIREBALL_LIFETIME))
+                    .insert(EdgeDespawns);
 
                 commands
                     .spawn(make_animation_bundle(
@@ -173,7 +198,7 @@ pub fn beholder_update(
                         1.,
                     ))
                     .insert(Projectile {
-                        dmg: 1,
+                        damage: DamageInfo::new(1, DamageType::Fire).with_source(entity),
                         damage_target: DamageTarget::Team(Team::Player),
                         piercing_mode: PiercingMode::None,
                         entities_hit: vec![],
@@ -182,7 +207,9 @@ pub fn beholder_update(
                     .insert(Velocity {
                         vec: (angle_to_target - Radian::from_degrees(10.)).unit_vector() * 40.,
                     })
-                    .insert(Collider::new_circle(15., transform.translation.truncate()));
+                    .insert(Collider::new_circle(15.).with_layer(CollisionLayer::EnemyProjectile))
+                    .insert(Lifetime::new(FIREBALL_LIFETIME))
+                    .insert(EdgeDespawns);
             }
         }
     }
@@ -213,7 +240,10 @@ pub fn spawn_beholder(
         .insert(MoveAndShootAI::new(20., 3., 200., 6. / 8., 2.))
         .insert(Velocity::ZERO)
         .insert(Health::new(25))
-        .insert(Collider::new_circle(12., Vec2 { x: 70., y: 70. }))
+        .insert(Collider::new_circle(12.).with_layer(CollisionLayer::Enemy))
+        .insert(Solid::new(2.))
+        .insert(StatusEffects::default())
+        .insert(Resistances::default().with(DamageType::Fire, -0.5))
         .insert(make_animation_bundle(
             BeholderAnimation::Flying,
             animations,
@@ -250,8 +280,19 @@ pub fn spawn_beholder_prince(
         .insert(BeholderPrince)
         .insert(MoveAndShootAI::new(20., 5., 300., 6. / 8., 3.))
         .insert(Velocity::ZERO)
-        .insert(Health::new(200))
-        .insert(Collider::new_circle(12., Vec2 { x: 100., y: 100. }))
+        .insert(Health::new(200).with_shield(Shield::new(
+            PRINCE_SHIELD,
+            PRINCE_SHIELD_RECHARGE_DELAY,
+            PRINCE_SHIELD_RECHARGE_TIME,
+        )))
+        .insert(Collider::new_circle(12.).with_layer(CollisionLayer::Enemy))
+        .insert(Solid::new(6.))
+        .insert(StatusEffects::default())
+        .insert(
+            Resistances::default()
+                .with(DamageType::Physical, 0.2)
+                .with(DamageType::Fire, -0.5),
+        )
         .insert(make_animation_bundle(
             BeholderAnimation::Flying,
             &animations,
@@ -3,13 +3,16 @@ use bevy::prelude::*;
 use bevy_kira_audio::AudioControl;
 use rand::Rng;
 
-use crate::animation::AnimationStateStorage;
+use crate::animation::{AnimationStateChangeEvent, AnimationStateStorage};
 use crate::audio::FXChannel;
-use crate::collision::collider::IsCollidingEvent;
 
+use crate::collision::collider::Collider;
 use crate::combat::health::DeathEvent;
+use crate::combat::iframes::IFrames;
 use crate::constants::SortingLayers;
 use crate::loading::{AudioAssets, TextureAssets};
+use crate::movement::pause::ActionPauseState;
+use crate::movement::velocity::Velocity;
 
 use crate::util::pitch_rng::PitchRNG;
 
@@ -17,6 +20,9 @@ use super::beholder::{spawn_beholder, BeholderAnimation};
 use super::imp::{spawn_imp, ImpAnimation};
 use super::reaper::{spawn_reaper, ReaperAnimation};
 
+// How long a dead enemy takes to fade out before it is despawned
+const DEATH_FADE_DURATION: f32 = 0.6;
+
 #[derive(Copy, Clone)]
 pub enum EnemyType {
     Imp,
@@ -54,6 +60,9 @@ pub struct Enemy {
     pub xp: u32,
 }
 
+#[derive(Component)]
+pub struct DeathFade(Timer);
+
 #[derive(Event)]
 pub struct EnemyDeathEvent {
     pub entity: Entity,
@@ -71,14 +80,15 @@ impl Enemy {
 pub fn death_loop(
     mut ememy_death_event: EventWriter<EnemyDeathEvent>,
     mut death_event: EventReader<DeathEvent>,
-    mut q_enemies: Query<(Entity, &Enemy, &Transform)>,
+    mut q_enemies: Query<(Entity, &Enemy, &Transform, Option<&mut Velocity>)>,
+    mut reaper_animation: EventWriter<AnimationStateChangeEvent<ReaperAnimation>>,
     fx_channel: Res<FXChannel>,
     audio: Res<AudioAssets>,
     mut pitch_rng: ResMut<PitchRNG>,
     mut commands: Commands,
 ) {
     for death_ev in death_event.iter() {
-        if let Ok((entity, enemy, transform)) = q_enemies.get_mut(death_ev.entity) {
+        if let Ok((entity, enemy, transform, velocity)) = q_enemies.get_mut(death_ev.entity) {
             fx_channel.play(match enemy.enemy_type {
                 EnemyType::Imp | EnemyType::ImpQueen => match pitch_rng.0 .0.gen_range(0..4) {
                     0 => audio.imp_death.clone(),
@@ -91,7 +101,27 @@ pub fn death_loop(
                 EnemyType::Reaper => audio.reaper_death.clone(),
             });
 
-            commands.entity(entity).despawn_recursive();
+            if let EnemyType::Reaper = enemy.enemy_type {
+                reaper_animation.send(AnimationStateChangeEvent {
+                    id: entity,
+                    state_id: ReaperAnimation::End,
+                });
+            }
+
+            if let Some(mut velocity) = velocity {
+                velocity.vec = Vec2::ZERO;
+            }
+
+            // The corpse can't be hit or touched anymore
+            commands
+                .entity(entity)
+                .remove::<Collider>()
+                .remove::<IFrames>()
+                .insert(DeathFade(Timer::from_seconds(
+                    DEATH_FADE_DURATION,
+                    TimerMode::Once,
+                )));
+
             ememy_death_event.send(EnemyDeathEvent {
                 entity,
                 enemy: enemy.clone(),
@@ -101,24 +131,26 @@ pub fn death_loop(
     }
 }
 
-pub fn spread_enemies(
-    mut collisions: EventReader<IsCollidingEvent>,
-    mut q_enemies: Query<&mut Transform, With<Enemy>>,
+// Dead enemies fade out and are despawned once they are invisible
+pub fn fade_dead_enemies(
+    mut q_corpses: Query<(Entity, &mut DeathFade, &mut TextureAtlasSprite)>,
+    pause: Res<ActionPauseState>,
+    time: Res<Time>,
+    mut commands: Commands,
 ) {
-    for collision_event in collisions.iter() {
-        if let Ok(mut entities) = q_enemies.get_many_mut([
-            collision_event.collision.entity_a,
-            collision_event.collision.entity_b,
-        ]) {
-            let force = 1.0;
-
-            let (slice_a, slice_b) = &mut entities.split_at_mut(1);
-            let a_transform = &mut slice_a[0];
-            let b_transform = &mut slice_b[0];
-            let diff = (a_transform.translation - b_transform.translation).normalize();
-            a_transform.translation += diff * force;
-            b_transform.translation -= diff * force;
+    if pause.is_paused {
+        return;
+    }
+
+    for (entity, mut fade, mut sprite) in q_corpses.iter_mut() {
+        fade.0.tick(time.delta());
+
+        if fade.0.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
         }
+
+        sprite.color.set_a(fade.0.percent_left());
     }
 }
 
@@ -6,10 +6,14 @@ use crate::{
     animation::{
         info::AnimationStateInfo, make_animation_bundle, Animation, AnimationStateStorage,
     },
-    collision::collider::Collider,
+    collision::{collider::Collider, layers::CollisionLayer, solid::Solid},
     combat::{
-        health::Health,
+        damage::{DamageInfo, DamageType, Resistances},
+        explosion::{Explodes, Explosion},
+        health::{Health, HealthType},
         healthbar::NeedsHealthBar,
+        projectile::DamageTarget,
+        status_effect::StatusEffects,
         teams::{Team, TeamMember},
     },
     loading::TextureAssets,
@@ -69,7 +73,10 @@ pub fn spawn_imp(
         })
         .insert(Velocity::ZERO)
         .insert(Health::new(15))
-        .insert(Collider::new_circle(10., position.truncate()))
+        .insert(Collider::new_circle(10.).with_layer(CollisionLayer::Enemy))
+        .insert(Solid::new(1.))
+        .insert(StatusEffects::default())
+        .insert(Resistances::default().with(DamageType::Fire, 0.5))
         .insert(make_animation_bundle(
             ImpAnimation::Flying,
             imp_animations,
@@ -81,6 +88,11 @@ pub fn spawn_imp(
         .insert(NeedsHealthBar::default());
 }
 
+// The queen bursts when she dies and hurts the player if they stand too close
+const QUEEN_BURST_RADIUS: f32 = 100.;
+const QUEEN_BURST_DAMAGE: HealthType = 1;
+const QUEEN_BURST_KNOCKBACK: f32 = 200.;
+
 #[derive(Component)]
 pub struct ImpQueen;
 
@@ -107,13 +119,27 @@ pub fn spawn_imp_queen(
             enemy_type: EnemyType::Imp,
         })
         .insert(ImpQueen)
+        .insert(
+            Explodes::new(
+                Explosion::new(
+                    QUEEN_BURST_RADIUS,
+                    DamageInfo::physical(QUEEN_BURST_DAMAGE),
+                    DamageTarget::Team(Team::Player),
+                )
+                .with_knockback(QUEEN_BURST_KNOCKBACK),
+            )
+            .on_death(),
+        )
         .insert(FollowPlayerAI {
             speed: 12.,
             corrective_force: 3.0,
         })
         .insert(Velocity::ZERO)
         .insert(Health::new(150))
-        .insert(Collider::new_circle(15., position.truncate()))
+        .insert(Collider::new_circle(15.).with_layer(CollisionLayer::Enemy))
+        .insert(Solid::new(4.))
+        .insert(StatusEffects::default())
+        .insert(Resistances::default().with(DamageType::Fire, 0.5))
         .insert(make_animation_bundle(
             ImpAnimation::Flying,
             &imp_animations,
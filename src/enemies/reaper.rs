@@ -10,13 +10,20 @@ use crate::{
         make_animation_bundle, Animation, AnimationStateChangeEvent, AnimationStateStorage,
     },
     audio::FXChannel,
-    collision::collider::Collider,
+    collision::{
+        collider::Collider,
+        layers::{CollisionLayer, CollisionLayers},
+        solid::Solid,
+    },
     combat::{
-        health::Health,
+        damage::{DamageInfo, DamageType, Resistances},
+        health::{Dead, Health},
         healthbar::NeedsHealthBar,
         projectile::{DamageTarget, PiercingMode, Projectile},
+        status_effect::StatusEffects,
         teams::{Team, TeamMember},
     },
+    constants::SCALING_VEC3,
     loading::{AudioAssets, TextureAssets},
     movement::velocity::Velocity,
     player::Player,
@@ -28,6 +35,10 @@ use super::{
     enemy::{Enemy, EnemyType},
 };
 
+// The blade's hitbox is a capsule lying along the swing, reaching as far as the old round one
+const BLADE_HALF_LENGTH: f32 = 26.;
+const BLADE_RADIUS: f32 = 24.;
+
 #[derive(PartialEq, Eq, Hash, Clone, Copy)]
 pub enum ReaperAnimation {
     Flying,
@@ -70,24 +81,28 @@ pub struct ReaperBlade {
 
 pub fn reaper_blade_update(
     mut q_blade: Query<(Entity, &mut ReaperBlade)>,
-    mut q_ai: Query<&mut MoveAndShootAI, Without<ReaperBlade>>,
+    mut q_ai: Query<&mut MoveAndShootAI, (Without<ReaperBlade>, Without<Dead>)>,
     time: Res<Time>,
     mut animate: EventWriter<AnimationStateChangeEvent<ReaperAnimation>>,
     mut commands: Commands,
 ) {
     for (entity, mut blade) in q_blade.iter_mut() {
+        // The swing ends with the reaper, a dead one keeps its death pose
+        let Ok(mut ai) = q_ai.get_mut(blade.parent) else {
+            commands.entity(entity).despawn();
+            continue;
+        };
+
         blade.timer.tick(time.delta());
 
         if blade.timer.just_finished() {
+            commands.entity(entity).despawn();
+
             animate.send(AnimationStateChangeEvent {
                 id: blade.parent,
                 state_id: ReaperAnimation::Flying,
             });
-            commands.entity(entity).despawn();
-
-            if let Ok(mut ai) = q_ai.get_mut(blade.parent) {
-                ai.speed = 40.;
-            }
+            ai.speed = 40.;
         }
     }
 }
@@ -138,7 +153,9 @@ pub fn reaper_update(
                 id: entity,
                 state_id: ReaperAnimation::End,
             });
-            let (player_entity, player_transform) = q_player.single();
+            let (_, player_transform) = q_player.single();
+            // The blade swings out towards the player
+            let swing = player_transform.translation.truncate() - transform.translation.truncate();
 
             fx.play(audio.blade.clone());
 
@@ -152,18 +169,31 @@ pub fn reaper_update(
                     transform.translation,
                     1.2,
                 ))
+                .insert(Transform {
+                    translation: transform.translation,
+                    rotation: Quat::from_rotation_z(swing.y.atan2(swing.x)),
+                    scale: SCALING_VEC3 * 1.2,
+                })
                 .insert(ReaperBlade {
                     parent: entity,
                     timer: Timer::from_seconds(0.5, TimerMode::Once),
                 })
                 .insert(Projectile {
-                    dmg: 1,
+                    damage: DamageInfo::physical(1).with_source(entity),
                     damage_target: DamageTarget::Team(Team::Player),
                     piercing_mode: PiercingMode::All,
                     entities_hit: vec![],
                     is_alive: true,
                 })
-                .insert(Collider::new_circle(50., transform.translation.truncate()));
+                .insert(
+                    // The blade is a melee swing and cuts through obstacles
+                    Collider::new_capsule(BLADE_HALF_LENGTH, BLADE_RADIUS).with_layers(
+                        CollisionLayers::new(
+                            &[CollisionLayer::EnemyProjectile],
+                            &[CollisionLayer::Player],
+                        ),
+                    ),
+                );
         }
     }
 }
@@ -193,7 +223,14 @@ pub fn spawn_reaper(
         .insert(MoveAndShootAI::new(40., 10., 50., 1. / 2., 2.))
         .insert(Velocity::ZERO)
         .insert(Health::new(300))
-        .insert(Collider::new_circle(12., Vec2 { x: 70., y: 70. }))
+        .insert(Collider::new_circle(12.).with_layer(CollisionLayer::Enemy))
+        .insert(Solid::new(8.))
+        .insert(StatusEffects::default())
+        .insert(
+            Resistances::default()
+                .with(DamageType::Physical, 0.25)
+                .with(DamageType::Fire, -0.25),
+        )
         .insert(make_animation_bundle(
             ReaperAnimation::Flying,
             animations,
@@ -1,4 +1,4 @@
-use bevy::{prelude::*, window::PrimaryWindow};
+use bevy::{prelude::*, sprite::Anchor, text::Text2dBounds, window::PrimaryWindow};
 use rand::seq::IteratorRandom;
 
 use crate::{
@@ -7,9 +7,9 @@ use crate::{
         info::{AnimationInfoBuilder, AnimationStateInfo},
         make_animation_bundle, Animation, AnimationStateChangeEvent, AnimationStateStorage,
     },
-    collision::collider::Collider,
-    combat::health::Health,
-    constants::SortingLayers,
+    collision::{collider::Collider, layers::CollisionLayer},
+    combat::health::{Health, Shield},
+    constants::{SortingLayers, DISTANCE_SCALING},
     loading::{AbilityTextures, FontAssets},
     movement::pause::ActionPauseState,
     palette::Palette,
@@ -25,6 +25,18 @@ use crate::{
 
 use super::experience::LevelUpEvent;
 
+// The shield granted by the first Shield ability, later ones add to its size
+const SHIELD_RECHARGE_DELAY: f32 = 5.;
+const SHIELD_RECHARGE_TIME: f32 = 3.;
+// Health regenerated per second by each Regeneration ability
+const REGENERATION_PER_STACK: f32 = 0.05;
+
+const NAME_FONT_SIZE: f32 = 24.;
+const DESCRIPTION_FONT_SIZE: f32 = 18.;
+// Below the frame's bottom edge, in the frame's unscaled pixels
+const DESCRIPTION_OFFSET: f32 = 20.;
+const DESCRIPTION_MARGIN: f32 = 16.;
+
 #[derive(Resource)]
 pub struct AbilityRNG(pub RNG);
 
@@ -101,6 +113,23 @@ pub fn on_select_ability(
                 health.value += 2;
             }
 
+            if selection.abilities[selection_ev.selected_index] == Ability::Shield {
+                match health.shield.as_mut() {
+                    Some(shield) => {
+                        shield.max += 1;
+                        shield.value += 1;
+                    }
+                    None => {
+                        health.shield =
+                            Some(Shield::new(1, SHIELD_RECHARGE_DELAY, SHIELD_RECHARGE_TIME))
+                    }
+                }
+            }
+
+            if selection.abilities[selection_ev.selected_index] == Ability::Regeneration {
+                health.regeneration += REGENERATION_PER_STACK;
+            }
+
             commmands.entity(entity).despawn_recursive();
             pause.is_paused = false;
         }
@@ -115,8 +144,8 @@ pub fn start_ability_selection(
     frame_animations: Res<AnimationStateStorage<AbilityFrameAnimation>>,
     mut texture_atlases: ResMut<Assets<TextureAtlas>>,
     mut rng: ResMut<AbilityRNG>,
-    _font_assets: Res<FontAssets>,
-    _palette: Res<Palette>,
+    font_assets: Res<FontAssets>,
+    palette: Res<Palette>,
     mut pause: ResMut<ActionPauseState>,
     mut commands: Commands,
 ) {
@@ -143,6 +172,9 @@ pub fn start_ability_selection(
     );
     let texture_atlas_handle = texture_atlases.add(texture_atlas);
 
+    // As wide as the space between the frames
+    let description_width = window.width() / 4. - DESCRIPTION_MARGIN;
+
     let all_abilities = Ability::all();
     let chosen_abilities = all_abilities
         .iter()
@@ -190,8 +222,10 @@ pub fn start_ability_selection(
                         index: IVec2 { x: i, y: 0 },
                     })
                     .with_children(|parent| {
+                        let ability = chosen_abilities[i as usize];
+
                         parent.spawn(SpriteBundle {
-                            texture: chosen_abilities[i as usize].get_texture(&textures),
+                            texture: ability.get_texture(&textures),
                             transform: Transform::from_translation(Vec3 {
                                 x: 0.,
                                 y: 0.,
@@ -199,9 +233,44 @@ pub fn start_ability_selection(
                             }),
                             ..Default::default()
                         });
+
+                        // Under the frame, scaled back so the text isn't stretched with it
+                        parent.spawn(Text2dBundle {
+                            text: Text::from_sections([
+                                TextSection::new(
+                                    format!("{}\n", ability.name()),
+                                    TextStyle {
+                                        font: font_assets.gothic_pxl.clone(),
+                                        font_size: NAME_FONT_SIZE,
+                                        color: palette.orange,
+                                    },
+                                ),
+                                TextSection::new(
+                                    ability.description(),
+                                    TextStyle {
+                                        font: font_assets.gothic_pxl.clone(),
+                                        font_size: DESCRIPTION_FONT_SIZE,
+                                        color: palette.white,
+                                    },
+                                ),
+                            ])
+                            .with_alignment(TextAlignment::Center),
+                            text_anchor: Anchor::TopCenter,
+                            text_2d_bounds: Text2dBounds {
+                                size: Vec2::new(description_width, f32::INFINITY),
+                            },
+                            transform: Transform {
+                                translation: Vec3::new(0., -DESCRIPTION_OFFSET, 0.),
+                                rotation: Quat::IDENTITY,
+                                scale: Vec3::ONE / DISTANCE_SCALING,
+                            },
+                            ..default()
+                        });
                     })
                     .insert(SelectionElement { index: i as usize })
-                    .insert(Collider::new_rect(Vec2 { x: 64., y: 64. }, Vec2::ZERO));
+                    .insert(
+                        Collider::new_rect(Vec2 { x: 64., y: 64. }).with_layer(CollisionLayer::UI),
+                    );
             }
         });
 }
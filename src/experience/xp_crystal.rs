@@ -47,31 +47,47 @@ pub fn drop_crystals(
     mut commands: Commands,
 ) {
     for death_ev in enemy_death_event.iter() {
-        for _ in 0..death_ev.enemy.xp {
-            let rng = &mut crystal_rng.0 .0;
-            let velocity: f32 = rng.gen_range(20.0..50.0);
-            let direction: Radian = Radian {
-                angle: rng.gen_range(Radian::ZERO.angle..Radian::FULL.angle),
-            };
+        spawn_crystals(
+            death_ev.location,
+            death_ev.enemy.xp,
+            &mut crystal_rng.0,
+            &textures,
+            &mut commands,
+        );
+    }
+}
 
-            commands
-                .spawn(XPCrystalBundle {
-                    sprite_bundle: SpriteBundle {
-                        transform: Transform {
-                            translation: death_ev.location,
-                            rotation: default(),
-                            scale: SCALING_VEC3,
-                        },
-                        texture: textures.crystal.clone(),
-                        ..Default::default()
+// Scatters the crystals around the location
+pub fn spawn_crystals(
+    location: Vec3,
+    amount: u32,
+    rng: &mut RNG,
+    textures: &TextureAssets,
+    commands: &mut Commands,
+) {
+    for _ in 0..amount {
+        let velocity: f32 = rng.0.gen_range(20.0..50.0);
+        let direction: Radian = Radian {
+            angle: rng.0.gen_range(Radian::ZERO.angle..Radian::FULL.angle),
+        };
+
+        commands
+            .spawn(XPCrystalBundle {
+                sprite_bundle: SpriteBundle {
+                    transform: Transform {
+                        translation: location,
+                        rotation: default(),
+                        scale: SCALING_VEC3,
                     },
-                    crystal: XPCrystal,
-                    velocity: (direction.unit_vector() * velocity).into(),
-                    friction: Friction { force: 50.0 },
-                    magnetic: FakeMagnetic { force: 1_000_000.0 },
-                })
-                .insert(EdgeTeleports);
-        }
+                    texture: textures.crystal.clone(),
+                    ..Default::default()
+                },
+                crystal: XPCrystal,
+                velocity: (direction.unit_vector() * velocity).into(),
+                friction: Friction { force: 50.0 },
+                magnetic: FakeMagnetic { force: 1_000_000.0 },
+            })
+            .insert(EdgeTeleports);
     }
 }
 
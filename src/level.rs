@@ -0,0 +1,35 @@
+use bevy::prelude::*;
+
+use self::{
+    obstacle::{obstacle_projectile_collision, spawn_obstacles},
+    prop::{break_props, create_prop_spawner, pickup_update, spawn_props},
+};
+use crate::{
+    combat::{health::check_death, projectile::projectile_collision_check},
+    GameState,
+};
+
+pub mod layout;
+pub mod obstacle;
+pub mod prop;
+
+pub struct LevelPlugin;
+
+impl Plugin for LevelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(GameState::Playing),
+            (spawn_obstacles, create_prop_spawner),
+        )
+        .add_systems(
+            Update,
+            (
+                obstacle_projectile_collision.after(projectile_collision_check),
+                spawn_props,
+                break_props.after(check_death),
+                pickup_update,
+            )
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
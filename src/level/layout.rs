@@ -0,0 +1,98 @@
+use bevy::prelude::*;
+
+// Compiled into the binary so the arena is also available on the web build
+pub const ARENA_LAYOUT: &str = include_str!("../../assets/levels/arena.level");
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ObstacleKind {
+    Pillar { radius: f32 },
+    Gravestone { size: Vec2 },
+    Wall { size: Vec2 },
+    // A regular polygon, the radius reaches its corners
+    Rock { radius: f32, sides: usize },
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ObstaclePlacement {
+    pub kind: ObstacleKind,
+    pub position: Vec2,
+    // In radians
+    pub rotation: f32,
+}
+
+/*
+Parses a level layout, every line is an obstacle like `wall <x> <y> <width> <height> [rotation]`.
+Empty lines and lines starting with # are ignored
+*/
+pub fn parse_layout(layout: &str) -> Result<Vec<ObstaclePlacement>, String> {
+    layout
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(line_number, line)| {
+            parse_obstacle(line).map_err(|err| format!("line {}: {}", line_number, err))
+        })
+        .collect()
+}
+
+fn parse_obstacle(line: &str) -> Result<ObstaclePlacement, String> {
+    let mut words = line.split_whitespace();
+    let kind = words.next().unwrap_or_default();
+    let numbers = words
+        .map(|word| {
+            word.parse::<f32>()
+                .map_err(|_| format!("`{}` is not a number", word))
+        })
+        .collect::<Result<Vec<f32>, String>>()?;
+
+    let (kind, rotation) = match (kind, numbers.as_slice()) {
+        ("pillar", [_, _, radius]) => (ObstacleKind::Pillar { radius: *radius }, 0.),
+        ("gravestone", [_, _, width, height]) => (
+            ObstacleKind::Gravestone {
+                size: Vec2::new(*width, *height),
+            },
+            0.,
+        ),
+        ("wall", [_, _, width, height]) => (
+            ObstacleKind::Wall {
+                size: Vec2::new(*width, *height),
+            },
+            0.,
+        ),
+        ("wall", [_, _, width, height, rotation]) => (
+            ObstacleKind::Wall {
+                size: Vec2::new(*width, *height),
+            },
+            rotation.to_radians(),
+        ),
+        ("rock", [_, _, radius, sides]) => (rock(*radius, *sides)?, 0.),
+        ("rock", [_, _, radius, sides, rotation]) => {
+            (rock(*radius, *sides)?, rotation.to_radians())
+        }
+        ("pillar" | "gravestone" | "wall" | "rock", _) => {
+            return Err(format!("wrong number of values for `{}`", kind))
+        }
+        _ => return Err(format!("unknown obstacle `{}`", kind)),
+    };
+
+    Ok(ObstaclePlacement {
+        kind,
+        position: Vec2::new(numbers[0], numbers[1]),
+        rotation,
+    })
+}
+
+fn rock(radius: f32, sides: f32) -> Result<ObstacleKind, String> {
+    if sides < 3. || sides.fract() != 0. {
+        return Err(format!(
+            "a rock needs a whole number of at least 3 sides, not {}",
+            sides
+        ));
+    }
+
+    Ok(ObstacleKind::Rock {
+        radius,
+        sides: sides as usize,
+    })
+}
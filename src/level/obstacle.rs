@@ -0,0 +1,288 @@
+use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
+
+use crate::{
+    collision::{
+        collider::{Collider, ColliderPose, CollisionStartEvent, ContinuousCollision},
+        geometry::{contact_normal, penetration},
+        layers::CollisionLayer,
+        solid::Solid,
+    },
+    combat::{
+        bounce::{reflect, Bounces},
+        projectile::Projectile,
+    },
+    constants::SortingLayers,
+    movement::velocity::Velocity,
+    palette::Palette,
+};
+
+use super::layout::{parse_layout, ObstacleKind, ObstaclePlacement, ARENA_LAYOUT};
+
+#[derive(Component)]
+pub struct Obstacle;
+
+pub fn spawn_obstacles(
+    palette: Res<Palette>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut commands: Commands,
+) {
+    let placements = match parse_layout(ARENA_LAYOUT) {
+        Ok(placements) => placements,
+        Err(err) => {
+            error!("Invalid arena layout, {}", err);
+            return;
+        }
+    };
+
+    for placement in placements {
+        spawn_obstacle(
+            placement,
+            &palette,
+            &mut meshes,
+            &mut materials,
+            &mut commands,
+        );
+    }
+}
+
+pub fn spawn_obstacle(
+    placement: ObstaclePlacement,
+    palette: &Palette,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<ColorMaterial>,
+    commands: &mut Commands,
+) {
+    let transform =
+        Transform::from_translation(placement.position.extend(SortingLayers::Terrain.into()))
+            .with_rotation(Quat::from_rotation_z(placement.rotation));
+
+    let mut obstacle = match placement.kind {
+        ObstacleKind::Pillar { radius } => {
+            let mut pillar = commands.spawn(MaterialMesh2dBundle {
+                mesh: meshes.add(shape::Circle::new(radius).into()).into(),
+                material: materials.add(ColorMaterial::from(palette.orange)),
+                transform,
+                ..default()
+            });
+            pillar.insert(Collider::new_circle(radius).with_layer(CollisionLayer::Obstacle));
+            pillar
+        }
+        ObstacleKind::Gravestone { size } | ObstacleKind::Wall { size } => {
+            let color = match placement.kind {
+                ObstacleKind::Gravestone { .. } => palette.white,
+                _ => palette.red,
+            };
+
+            let mut block = commands.spawn(SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(size),
+                    ..default()
+                },
+                transform,
+                ..default()
+            });
+            block.insert(Collider::new_rect(size).with_layer(CollisionLayer::Obstacle));
+            block
+        }
+        ObstacleKind::Rock { radius, sides } => {
+            let mut rock = commands.spawn(MaterialMesh2dBundle {
+                mesh: meshes
+                    .add(shape::RegularPolygon::new(radius, sides).into())
+                    .into(),
+                material: materials.add(ColorMaterial::from(palette.white)),
+                transform,
+                ..default()
+            });
+            rock.insert(
+                Collider::new_polygon(rock_vertices(radius, sides))
+                    .with_layer(CollisionLayer::Obstacle),
+            );
+            rock
+        }
+    };
+
+    obstacle.insert(Obstacle).insert(Solid::STATIC);
+}
+
+// The corners of the rock's mesh, which starts at the top, but counter clockwise
+fn rock_vertices(radius: f32, sides: usize) -> Vec<Vec2> {
+    let step = std::f32::consts::TAU / sides as f32;
+    (0..sides)
+        .map(|i| Vec2::from_angle(std::f32::consts::FRAC_PI_2 + i as f32 * step) * radius)
+        .collect()
+}
+
+// Projectiles break on obstacles
+pub fn obstacle_projectile_collision(
+    q_obstacles: Query<(&Collider, &Transform), (With<Obstacle>, Without<Projectile>)>,
+    mut q_projectiles: Query<(
+        &mut Projectile,
+        &Collider,
+        &mut Transform,
+        Option<&mut Bounces>,
+        Option<&mut Velocity>,
+        Option<&mut ContinuousCollision>,
+    )>,
+    mut ev_collision: EventReader<CollisionStartEvent>,
+    mut commands: Commands,
+) {
+    for ev in ev_collision.iter() {
+        let (entity_a, entity_b) = (ev.collision.entity_a, ev.collision.entity_b);
+
+        for (projectile_entity, obstacle_entity) in [(entity_a, entity_b), (entity_b, entity_a)] {
+            let Ok((obstacle_collider, obstacle_transform)) = q_obstacles.get(obstacle_entity)
+            else {
+                continue;
+            };
+
+            let Ok((mut projectile, collider, mut transform, bounces, velocity, continuous)) =
+                q_projectiles.get_mut(projectile_entity)
+            else {
+                continue;
+            };
+
+            if !projectile.is_alive {
+                continue;
+            }
+
+            if let (Some(mut bounces), Some(mut velocity)) = (bounces, velocity) {
+                if bounces.consume() {
+                    // Fast projectiles may have passed the surface already, they bounce where they hit it
+                    if let (Some(mut continuous), Some(time_of_impact)) =
+                        (continuous, ev.collision.time_of_impact)
+                    {
+                        continuous.rewind(time_of_impact, &mut transform);
+                    }
+
+                    let core = collider.core(ColliderPose::from(transform.as_ref()));
+                    let obstacle_core =
+                        obstacle_collider.core(ColliderPose::from(obstacle_transform));
+                    let push = penetration(&core, &obstacle_core);
+                    let normal = contact_normal(&core, &obstacle_core);
+
+                    transform.translation += push.unwrap_or(Vec2::ZERO).extend(0.);
+                    velocity.vec = reflect(velocity.vec, normal);
+                    continue;
+                }
+            }
+
+            projectile.is_alive = false;
+            commands.entity(projectile_entity).despawn();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        collision::collider::{collision_tick, tests::collision_app},
+        combat::{
+            damage::DamageInfo,
+            health::{Health, TookDamageEvent},
+            projectile::{
+                projectile_collision_check, DamageTarget, PiercingMode, ProjectileHitEvent,
+            },
+            teams::{Team, TeamMember},
+        },
+    };
+
+    fn spawn_bullet(app: &mut App) -> Entity {
+        app.world
+            .spawn((
+                Projectile {
+                    damage_target: DamageTarget::Team(Team::Enemy),
+                    damage: DamageInfo::physical(1),
+                    piercing_mode: PiercingMode::None,
+                    entities_hit: vec![],
+                    is_alive: true,
+                },
+                Collider::new_circle(5.).with_layer(CollisionLayer::PlayerProjectile),
+                ContinuousCollision::default(),
+                Velocity {
+                    vec: Vec2::new(12000., 0.),
+                },
+                Transform::default(),
+            ))
+            .id()
+    }
+
+    // Spans 90 to 110 on the x axis
+    fn spawn_wall(app: &mut App) {
+        app.world.spawn((
+            Obstacle,
+            Collider::new_rect(Vec2::new(20., 200.)).with_layer(CollisionLayer::Obstacle),
+            Transform::from_xyz(100., 0., 0.),
+        ));
+    }
+
+    #[test]
+    fn fast_projectiles_bounce_off_the_side_they_hit() {
+        let mut app = collision_app();
+        app.add_systems(Update, obstacle_projectile_collision.after(collision_tick));
+
+        spawn_wall(&mut app);
+        let bullet = spawn_bullet(&mut app);
+        app.world.entity_mut(bullet).insert(Bounces::new(1));
+
+        app.update();
+
+        // Skips past the wall in a single step
+        app.world
+            .get_mut::<Transform>(bullet)
+            .unwrap()
+            .translation
+            .x = 200.;
+        app.update();
+
+        let bullet = app.world.entity(bullet);
+        assert!(bullet.get::<Projectile>().unwrap().is_alive);
+        assert_eq!(bullet.get::<Bounces>().unwrap().remaining, 0);
+        assert!(bullet.get::<Velocity>().unwrap().vec.x < 0.);
+
+        // Back on the side it came from, touching the wall
+        let x = bullet.get::<Transform>().unwrap().translation.x;
+        assert!((84.9..90.).contains(&x), "bullet at {x}");
+    }
+
+    #[test]
+    fn fast_projectiles_dont_hit_what_is_behind_a_wall() {
+        let mut app = collision_app();
+        app.add_event::<ProjectileHitEvent>()
+            .add_event::<TookDamageEvent>()
+            .add_systems(
+                Update,
+                (
+                    projectile_collision_check.after(collision_tick),
+                    obstacle_projectile_collision.after(projectile_collision_check),
+                ),
+            );
+
+        spawn_wall(&mut app);
+        let enemy = app
+            .world
+            .spawn((
+                Health::new(10),
+                TeamMember { team: Team::Enemy },
+                Collider::new_circle(10.).with_layer(CollisionLayer::Enemy),
+                Transform::from_xyz(200., 0., 0.),
+            ))
+            .id();
+        let bullet = spawn_bullet(&mut app);
+
+        app.update();
+
+        // Sweeps through the wall and ends up on the enemy
+        app.world
+            .get_mut::<Transform>(bullet)
+            .unwrap()
+            .translation
+            .x = 200.;
+        app.update();
+
+        assert_eq!(app.world.get::<Health>(enemy).unwrap().value, 10);
+        assert!(app.world.get_entity(bullet).is_none());
+    }
+}
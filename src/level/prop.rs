@@ -0,0 +1,363 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+use bevy_kira_audio::AudioControl;
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{
+    audio::FXChannel,
+    collision::{
+        collider::Collider,
+        layers::CollisionLayer,
+        solid::Solid,
+        spatial_query::{SpatialQuery, SpatialQueryFilter},
+    },
+    combat::{
+        health::{DeathEvent, Health, HealthType},
+        healthbar::NeedsHealthBar,
+        teams::{Team, TeamMember},
+    },
+    constants::{SortingLayers, SCALING_VEC3},
+    experience::xp_crystal::{spawn_crystals, CrystalRNG},
+    loading::{AudioAssets, TextureAssets},
+    movement::pause::ActionPauseState,
+    palette::Palette,
+    player::{
+        buff::{Buff, BuffKind},
+        Player,
+    },
+    util::rng::{GlobalSeed, RNG},
+};
+
+const PROP_SPAWN_INTERVAL: f32 = 10.;
+const MAX_PROPS: usize = 5;
+// Props keep away from the arena edges, where enemies come in
+const SPAWN_MARGIN: f32 = 64.;
+// And from the player and everything else that's solid
+const SPAWN_CLEARANCE: f32 = 120.;
+const SPAWN_ATTEMPTS: u32 = 8;
+
+const PICKUP_DISTANCE: f32 = 16.;
+// Loot that isn't picked up disappears after a while
+const PICKUP_DURATION: f32 = 15.;
+const BUFF_DURATION: f32 = 10.;
+const FRENZY_COLOR: Color = Color::rgb(1., 0.9, 0.3);
+const MIGHT_COLOR: Color = Color::rgb(0.9, 0.4, 0.9);
+
+#[derive(Clone, Copy)]
+pub enum PropKind {
+    Crate,
+    Urn,
+    Coffin,
+}
+
+impl PropKind {
+    pub fn all() -> Vec<PropKind> {
+        vec![PropKind::Crate, PropKind::Urn, PropKind::Coffin]
+    }
+
+    fn health(&self) -> HealthType {
+        match self {
+            PropKind::Crate => 15,
+            PropKind::Urn => 5,
+            PropKind::Coffin => 40,
+        }
+    }
+
+    fn size(&self) -> Vec2 {
+        match self {
+            PropKind::Crate => Vec2::new(24., 24.),
+            PropKind::Urn => Vec2::new(14., 20.),
+            PropKind::Coffin => Vec2::new(18., 40.),
+        }
+    }
+
+    fn color(&self, palette: &Palette) -> Color {
+        match self {
+            PropKind::Crate => palette.orange,
+            PropKind::Urn => palette.white,
+            PropKind::Coffin => palette.red,
+        }
+    }
+
+    // The bigger the prop, the better the loot
+    fn roll_loot(&self, rng: &mut RNG) -> Vec<Loot> {
+        let mut loot = vec![];
+
+        match self {
+            PropKind::Crate => {
+                loot.push(Loot::Experience(rng.0.gen_range(3..=6)));
+                if rng.0.gen_bool(0.2) {
+                    loot.push(Loot::Buff(*BuffKind::all().choose(&mut rng.0).unwrap()));
+                }
+            }
+            PropKind::Urn => {
+                loot.push(Loot::Experience(rng.0.gen_range(1..=3)));
+                if rng.0.gen_bool(0.25) {
+                    loot.push(Loot::Heart);
+                }
+            }
+            PropKind::Coffin => {
+                loot.push(Loot::Experience(rng.0.gen_range(8..=12)));
+                loot.push(Loot::Buff(*BuffKind::all().choose(&mut rng.0).unwrap()));
+                if rng.0.gen_bool(0.3) {
+                    loot.push(Loot::Heart);
+                }
+            }
+        }
+
+        loot
+    }
+}
+
+enum Loot {
+    Experience(u32),
+    Heart,
+    Buff(BuffKind),
+}
+
+/*
+Neutral obstacles that block movement and break from any projectile, dropping loot
+*/
+#[derive(Component)]
+pub struct Prop {
+    pub kind: PropKind,
+}
+
+#[derive(Clone, Copy)]
+pub enum PickupKind {
+    Heart,
+    Buff(BuffKind),
+}
+
+#[derive(Component)]
+pub struct Pickup {
+    pub kind: PickupKind,
+    timer: Timer,
+}
+
+impl Pickup {
+    fn new(kind: PickupKind) -> Pickup {
+        Pickup {
+            kind,
+            timer: Timer::from_seconds(PICKUP_DURATION, TimerMode::Once),
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct PropSpawner {
+    pub timer: Timer,
+    rng: RNG,
+}
+
+pub fn create_prop_spawner(seed: Res<GlobalSeed>, mut commands: Commands) {
+    commands.insert_resource(PropSpawner {
+        timer: Timer::from_seconds(PROP_SPAWN_INTERVAL, TimerMode::Repeating),
+        rng: RNG::new(seed.0.as_str(), "prop_rng"),
+    })
+}
+
+pub fn spawn_props(
+    q_props: Query<(), With<Prop>>,
+    q_player: Query<&Transform, With<Player>>,
+    q_windows: Query<&Window, With<PrimaryWindow>>,
+    spatial_query: SpatialQuery,
+    mut spawner: ResMut<PropSpawner>,
+    palette: Res<Palette>,
+    pause: Res<ActionPauseState>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    if pause.is_paused {
+        return;
+    }
+
+    spawner.timer.tick(time.delta());
+    if !spawner.timer.just_finished() || q_props.iter().count() >= MAX_PROPS {
+        return;
+    }
+
+    let window = q_windows.single();
+    let half_arena = Vec2::new(window.width(), window.height()) / 2. - SPAWN_MARGIN;
+    let player_position = q_player.single().translation.truncate();
+    let filter = SpatialQueryFilter::default().with_layers(&[
+        CollisionLayer::Obstacle,
+        CollisionLayer::Enemy,
+        CollisionLayer::Player,
+    ]);
+
+    let kind = *PropKind::all().choose(&mut spawner.rng.0).unwrap();
+
+    // Gives up until the next spawn if the arena is too crowded
+    for _ in 0..SPAWN_ATTEMPTS {
+        let position = Vec2::new(
+            spawner.rng.0.gen_range(-half_arena.x..half_arena.x),
+            spawner.rng.0.gen_range(-half_arena.y..half_arena.y),
+        );
+
+        if position.distance(player_position) < SPAWN_CLEARANCE
+            || !spatial_query
+                .circle_query(position, SPAWN_CLEARANCE / 2., &filter)
+                .is_empty()
+        {
+            continue;
+        }
+
+        spawn_prop(kind, position, &palette, &mut commands);
+        return;
+    }
+}
+
+pub fn spawn_prop(kind: PropKind, position: Vec2, palette: &Palette, commands: &mut Commands) {
+    commands
+        .spawn(SpriteBundle {
+            sprite: Sprite {
+                color: kind.color(palette),
+                custom_size: Some(kind.size()),
+                ..default()
+            },
+            transform: Transform::from_translation(position.extend(SortingLayers::Terrain.into())),
+            ..default()
+        })
+        .insert(Prop { kind })
+        .insert(Health::new(kind.health()))
+        .insert(TeamMember { team: Team::None })
+        .insert(Collider::new_rect(kind.size()).with_layer(CollisionLayer::Obstacle))
+        .insert(Solid::STATIC)
+        .insert(NeedsHealthBar::default());
+}
+
+pub fn break_props(
+    q_props: Query<(&Prop, &Transform)>,
+    mut death_ev: EventReader<DeathEvent>,
+    mut spawner: ResMut<PropSpawner>,
+    mut crystal_rng: ResMut<CrystalRNG>,
+    textures: Res<TextureAssets>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    mut commands: Commands,
+) {
+    for death in death_ev.iter() {
+        let Ok((prop, transform)) = q_props.get(death.entity) else {
+            continue;
+        };
+
+        commands.entity(death.entity).despawn_recursive();
+
+        let location = transform.translation.truncate();
+        for loot in prop.kind.roll_loot(&mut spawner.rng) {
+            match loot {
+                Loot::Experience(amount) => spawn_crystals(
+                    location.extend(SortingLayers::Action.into()),
+                    amount,
+                    &mut crystal_rng.0,
+                    &textures,
+                    &mut commands,
+                ),
+                Loot::Heart => {
+                    let texture_atlas = TextureAtlas::from_grid(
+                        textures.heart_ui.clone(),
+                        Vec2 { x: 16., y: 16. },
+                        2,
+                        1,
+                        None,
+                        None,
+                    );
+
+                    // Scattered a bit so it doesn't hide under the crystals
+                    let offset = Vec2::new(
+                        spawner.rng.0.gen_range(-12.0..12.0),
+                        spawner.rng.0.gen_range(-12.0..12.0),
+                    );
+
+                    commands
+                        .spawn(SpriteSheetBundle {
+                            texture_atlas: texture_atlases.add(texture_atlas),
+                            sprite: TextureAtlasSprite::new(0),
+                            transform: Transform {
+                                translation: (location + offset)
+                                    .extend(SortingLayers::Action.into()),
+                                rotation: default(),
+                                scale: SCALING_VEC3,
+                            },
+                            ..default()
+                        })
+                        .insert(Pickup::new(PickupKind::Heart));
+                }
+                Loot::Buff(kind) => {
+                    let offset = Vec2::new(
+                        spawner.rng.0.gen_range(-12.0..12.0),
+                        spawner.rng.0.gen_range(-12.0..12.0),
+                    );
+
+                    // Shares the crystal sprite until buffs get their own
+                    commands
+                        .spawn(SpriteBundle {
+                            texture: textures.crystal.clone(),
+                            sprite: Sprite {
+                                color: match kind {
+                                    BuffKind::Frenzy => FRENZY_COLOR,
+                                    BuffKind::Might => MIGHT_COLOR,
+                                },
+                                ..default()
+                            },
+                            transform: Transform {
+                                translation: (location + offset)
+                                    .extend(SortingLayers::Action.into()),
+                                rotation: default(),
+                                scale: SCALING_VEC3 * 1.5,
+                            },
+                            ..default()
+                        })
+                        .insert(Pickup::new(PickupKind::Buff(kind)));
+                }
+            }
+        }
+    }
+}
+
+pub fn pickup_update(
+    mut q_pickups: Query<(Entity, &mut Pickup, &Transform), Without<Player>>,
+    mut q_player: Query<(&mut Player, &mut Health, &Transform)>,
+    fx_channel: Res<FXChannel>,
+    audio: Res<AudioAssets>,
+    pause: Res<ActionPauseState>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    if pause.is_paused {
+        return;
+    }
+
+    let (mut player, mut health, player_transform) = q_player.single_mut();
+
+    for (entity, mut pickup, transform) in q_pickups.iter_mut() {
+        pickup.timer.tick(time.delta());
+
+        if pickup.timer.finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        if transform
+            .translation
+            .truncate()
+            .distance(player_transform.translation.truncate())
+            > PICKUP_DISTANCE
+        {
+            continue;
+        }
+
+        match pickup.kind {
+            PickupKind::Heart => {
+                // Left lying around for later when at full health
+                if health.value >= health.max {
+                    continue;
+                }
+                health.value += 1;
+            }
+            PickupKind::Buff(kind) => player.add_buff(Buff::new(kind, BUFF_DURATION)),
+        }
+
+        commands.entity(entity).despawn();
+        fx_channel.play(audio.coin.clone());
+    }
+}
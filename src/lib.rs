@@ -7,8 +7,11 @@ mod cheats;
 mod collision;
 mod combat;
 mod constants;
+#[cfg(debug_assertions)]
+mod debug_overlay;
 mod enemies;
 mod experience;
+mod level;
 mod loading;
 mod menu;
 mod movement;
@@ -33,6 +36,7 @@ use cheats::CheatsPlugin;
 use collision::CollisionPlugin;
 use combat::CombatPlugin;
 use experience::ExperiencePlugin;
+use level::LevelPlugin;
 use movement::MovementPlugin;
 use ui::UIPlugin;
 use util::UtilPlugin;
@@ -65,6 +69,7 @@ impl Plugin for GamePlugin {
             CombatPlugin,
             EnemiesPlugin,
             CollisionPlugin,
+            LevelPlugin,
             ExperiencePlugin,
             UtilPlugin,
             MovementPlugin,
@@ -74,7 +79,11 @@ impl Plugin for GamePlugin {
 
         #[cfg(debug_assertions)]
         {
-            app.add_plugins((FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin::default()));
+            app.add_plugins((
+                FrameTimeDiagnosticsPlugin,
+                LogDiagnosticsPlugin::default(),
+                debug_overlay::DebugOverlayPlugin,
+            ));
         }
     }
 }
@@ -1,6 +1,6 @@
 use bevy::{prelude::*, window::PrimaryWindow};
 
-const EDGE_GRACE: f32 = 50.0;
+pub const EDGE_GRACE: f32 = 50.0;
 
 #[derive(Component)]
 pub struct EdgeTeleports;
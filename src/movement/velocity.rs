@@ -1,6 +1,6 @@
 use bevy::prelude::*;
 
-use crate::constants::DISTANCE_SCALING;
+use crate::{combat::status_effect::StatusEffects, constants::DISTANCE_SCALING};
 
 use super::pause::ActionPauseState;
 
@@ -14,7 +14,7 @@ impl Velocity {
 }
 
 pub fn velocity_update(
-    mut q_velocity: Query<(&Velocity, &mut Transform)>,
+    mut q_velocity: Query<(&Velocity, &mut Transform, Option<&StatusEffects>)>,
     time: Res<Time>,
     pause_state: Res<ActionPauseState>,
 ) {
@@ -22,13 +22,16 @@ pub fn velocity_update(
         return;
     }
 
-    for (velocity, mut transform) in q_velocity.iter_mut() {
+    for (velocity, mut transform, status_effects) in q_velocity.iter_mut() {
+        let speed_multiplier = status_effects.map_or(1., StatusEffects::speed_multiplier);
+
         transform.translation += Vec3 {
             x: velocity.vec.x,
             y: velocity.vec.y,
             z: 0.,
         } * time.delta_seconds()
-            * DISTANCE_SCALING;
+            * DISTANCE_SCALING
+            * speed_multiplier;
     }
 }
 
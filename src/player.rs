@@ -3,21 +3,30 @@ use crate::animation::controller::AnimationController;
 use crate::animation::{make_animation_bundle, AnimationStateChangeEvent, AppAnimationSetup};
 use crate::audio::FXChannel;
 use crate::collision::collider::{Collider, IsCollidingEvent};
+use crate::collision::layers::CollisionLayer;
+use crate::collision::solid::Solid;
+use crate::combat::bomb::Bomb;
+use crate::combat::damage::{DamageInfo, Resistances};
+use crate::combat::explosion::ExplosionVisual;
 use crate::combat::fire::Fire;
-use crate::combat::health::{DeathEvent, Health, HealthType, TookDamageEvent};
-use crate::combat::projectile::{projectile_collision_check, Projectile};
+use crate::combat::health::{Dead, DeathEvent, Health, HealthType, TookDamageEvent};
+use crate::combat::iframes::{iframes_update, IFrames};
+use crate::combat::projectile::Projectile;
+use crate::combat::status_effect::StatusEffects;
 use crate::combat::teams::{Team, TeamMember};
 use crate::constants::SortingLayers;
 use crate::enemies::enemy::Enemy;
 use crate::enemies::spawning::SpawnInfo;
 use crate::experience::experience::Experience;
 use crate::experience::xp_crystal::XPCrystal;
+use crate::level::prop::{Pickup, Prop};
 use crate::loading::{AudioAssets, FontAssets, TextureAssets};
 use crate::movement::edge_teleport::EdgeTeleports;
 use crate::movement::pause::ActionPauseState;
 use crate::palette::Palette;
 use crate::ui::game_timer::GameTimer;
 use crate::util::pitch_rng::PitchRNG;
+use crate::util::rng::RNG;
 use crate::GameState;
 use bevy::prelude::*;
 
@@ -26,41 +35,87 @@ use rand::Rng;
 
 use self::ability::Ability;
 use self::animations::{PlayerAnimationState, PlayerAnimations};
+use self::buff::{update_buffs, Buff};
 use self::bullets_ui::{manage_bullet_ui_sprites, BulletUIAnimation, BulletUICount};
-use self::health_ui::{manage_health_ui_sprites, HealthUIAnimationState, HealthUICount};
+use self::health_ui::{
+    manage_health_ui_sprites, manage_shield_ui_sprites, HealthUIAnimationState, HealthUICount,
+    ShieldUICount,
+};
 use self::hit::{spawn_hit_sprite, update_hit_sprite};
 use self::reload_ui::{spawn_reload_ui, update_reload_ui, ReloadTimer};
 use self::shooting::{shoot, ShootingCooldown};
+use self::thorns::{thorns, update_thorns_bursts};
 
 pub mod ability;
 mod animations;
+pub mod buff;
 mod bullets_ui;
 mod health_ui;
 mod hit;
 mod reload_ui;
 mod shooting;
+mod thorns;
 
 pub struct PlayerPlugin;
 
+// Invulnerability after getting hit
+const PLAYER_IFRAMES: f32 = 3.;
+
+// Loading a single shell takes this fraction of a full reload
+const SHELL_RELOAD_FRACTION: f32 = 0.25;
+
+// Ricochets needed before bullets turn towards the next enemy after bouncing off one
+const RETARGET_RICOCHETS: u32 = 2;
+
 #[derive(Component)]
 pub struct Player {
     curr_bullets: u32,
     max_bullets: u32,
     is_reloading: bool,
     pub abilities: Vec<Ability>,
+    pub buffs: Vec<Buff>,
 }
 
 impl Player {
     pub fn damage(&self) -> HealthType {
+        let damage = self
+            .abilities
+            .iter()
+            .fold(5., |dmg, ability| dmg * ability.damage_mult());
+        self.buffs
+            .iter()
+            .fold(damage, |dmg, buff| dmg * buff.kind.damage_mult()) as u32
+    }
+
+    pub fn crit_chance(&self) -> f32 {
         self.abilities
             .iter()
-            .fold(5., |dmg, ability| dmg * ability.damage_mult()) as u32
+            .fold(0.05, |chance, ability| chance + ability.crit_chance_bonus())
+            .min(1.)
     }
 
-    pub fn shoot_time(&self) -> f32 {
+    pub fn crit_multiplier(&self) -> f32 {
         self.abilities
             .iter()
-            .fold(0.5, |dmg, ability| dmg / ability.shoot_speed_mult())
+            .fold(1.5, |mult, ability| mult + ability.crit_mult_bonus())
+    }
+
+    // Rolls whether the damage is a crit
+    pub fn roll_crit(&self, damage: DamageInfo, rng: &mut RNG) -> DamageInfo {
+        match rng.0.gen_bool(self.crit_chance() as f64) {
+            true => damage.crit(self.crit_multiplier()),
+            false => damage,
+        }
+    }
+
+    pub fn shoot_time(&self) -> f32 {
+        let shoot_time = self
+            .abilities
+            .iter()
+            .fold(0.5, |dmg, ability| dmg / ability.shoot_speed_mult());
+        self.buffs
+            .iter()
+            .fold(shoot_time, |time, buff| time / buff.kind.shoot_speed_mult())
     }
 
     pub fn reload_time(&self) -> f32 {
@@ -69,6 +124,23 @@ impl Player {
             .fold(1.0, |dmg, ability| dmg / ability.reload_mult())
     }
 
+    // With shells the magazine is loaded one shell at a time
+    pub fn shell_reload_time(&self) -> f32 {
+        self.reload_time() * SHELL_RELOAD_FRACTION
+    }
+
+    // Every ricochet lets bullets bounce once more
+    pub fn bounces(&self) -> u32 {
+        self.abilities
+            .iter()
+            .filter(|ability| ability == &&Ability::Ricochet)
+            .count() as u32
+    }
+
+    pub fn bounces_retarget(&self) -> bool {
+        self.bounces() >= RETARGET_RICOCHETS
+    }
+
     pub fn knockback(&self) -> f32 {
         self.abilities
             .iter()
@@ -91,25 +163,24 @@ impl Plugin for PlayerPlugin {
                 shoot,
                 manage_bullet_ui_sprites,
                 manage_health_ui_sprites,
+                manage_shield_ui_sprites,
                 update_reload_ui,
                 game_over,
                 click_play_again_button,
-                enemy_collision,
+                enemy_collision.before(iframes_update),
                 update_hit_sprite,
                 update_bullets,
-                hit_immunity
-                    .after(projectile_collision_check)
-                    .after(enemy_collision),
+                thorns.before(iframes_update),
+                update_thorns_bursts,
+                hurt_sound,
+                update_buffs,
             )
                 .run_if(in_state(GameState::Playing)),
         )
         .insert_resource(ReloadTimer(Timer::from_seconds(0., TimerMode::Once)))
         .insert_resource(BulletUICount(0))
         .insert_resource(HealthUICount(0))
-        .insert_resource(InvincibilityTimer(Timer::from_seconds(
-            3.0,
-            TimerMode::Once,
-        )))
+        .insert_resource(ShieldUICount(0))
         .insert_resource(ShootingCooldown(Timer::from_seconds(1.0, TimerMode::Once)))
         .add_animation::<PlayerAnimationState>()
         .add_animation::<BulletUIAnimation>()
@@ -139,8 +210,11 @@ pub fn spawn_player(
             curr_bullets: 6,
             is_reloading: false,
             abilities: vec![],
+            buffs: vec![],
         })
-        .insert(Collider::new_circle(10., Vec2 { x: 0., y: 0. }))
+        .insert(Collider::new_circle(10.).with_layer(CollisionLayer::Player))
+        .insert(Solid::new(5.))
+        .insert(StatusEffects::default())
         .insert(make_animation_bundle(
             PlayerAnimationState::Idle,
             &player_animations,
@@ -160,6 +234,7 @@ pub fn spawn_player(
         })
         .insert(EdgeTeleports)
         .insert(Health::new(3))
+        .insert(IFrames::new(PLAYER_IFRAMES))
         .insert(TeamMember { team: Team::Player });
 }
 
@@ -232,30 +307,18 @@ fn move_player(
     }
 }
 
-#[derive(Resource)]
-pub struct InvincibilityTimer(Timer);
-
-pub fn hit_immunity(
-    mut q_player: Query<(Entity, &mut Health), With<Player>>,
-    mut timer: ResMut<InvincibilityTimer>,
+pub fn hurt_sound(
+    q_player: Query<Entity, With<Player>>,
     mut ev_dmg: EventReader<TookDamageEvent>,
-    time: Res<Time>,
     audio_assets: Res<AudioAssets>,
     fx_channel: Res<FXChannel>,
     mut pitch_rng: ResMut<PitchRNG>,
 ) {
-    timer.0.tick(time.delta());
-    let (player_entity, mut player_health) = q_player.single_mut();
-
-    if timer.0.just_finished() {
-        player_health.is_invincible = false;
-    }
+    let player_entity = q_player.single();
 
     for took_dmg in ev_dmg.iter() {
-        if took_dmg.entity == player_entity {
-            player_health.is_invincible = true;
-            timer.0.reset();
-
+        // Hits the shield fully soaks up don't hurt
+        if took_dmg.entity == player_entity && took_dmg.amount > took_dmg.absorbed {
             fx_channel
                 .play(audio_assets.grunt.clone())
                 .with_playback_rate(pitch_rng.0 .0.gen_range(0.9..1.1));
@@ -264,8 +327,16 @@ pub fn hit_immunity(
 }
 
 pub fn enemy_collision(
-    mut q_player: Query<(Entity, &mut Health), With<Player>>,
-    q_enemies: Query<Entity, With<Enemy>>,
+    mut q_player: Query<
+        (
+            Entity,
+            &mut Health,
+            Option<&Resistances>,
+            Option<&StatusEffects>,
+        ),
+        With<Player>,
+    >,
+    q_enemies: Query<Entity, (With<Enemy>, Without<Dead>)>,
     mut collisions: EventReader<IsCollidingEvent>,
     mut ev_dmg: EventWriter<TookDamageEvent>,
     pause: Res<ActionPauseState>,
@@ -274,26 +345,32 @@ pub fn enemy_collision(
         return;
     }
 
-    let (player, mut health) = q_player.single_mut();
-    let mut is_hit = false;
+    let (player, mut health, resistances, status_effects) = q_player.single_mut();
+    let mut hit_by = None;
 
     for ev in collisions.iter() {
-        if let Ok(_) = q_enemies.get(ev.collision.entity_a) {
+        if let Ok(enemy) = q_enemies.get(ev.collision.entity_a) {
             if player == ev.collision.entity_b {
-                is_hit = true;
+                hit_by = Some(enemy);
                 break;
             }
         }
-        if let Ok(_) = q_enemies.get(ev.collision.entity_b) {
+        if let Ok(enemy) = q_enemies.get(ev.collision.entity_b) {
             if player == ev.collision.entity_a {
-                is_hit = true;
+                hit_by = Some(enemy);
                 break;
             }
         }
     }
 
-    if is_hit {
-        health.take_damage(player, &mut ev_dmg, 1);
+    if let Some(enemy) = hit_by {
+        health.take_damage(
+            player,
+            &mut ev_dmg,
+            DamageInfo::physical(1).with_source(enemy),
+            resistances,
+            status_effects,
+        );
     }
 }
 
@@ -406,7 +483,15 @@ fn click_play_again_button(
         (Changed<Interaction>, With<Button>),
     >,
     mut q_player: Query<
-        (&mut Player, &mut Transform, &mut Health, &mut Experience),
+        (
+            Entity,
+            &mut Player,
+            &mut Transform,
+            &mut Health,
+            &mut Experience,
+            &mut StatusEffects,
+            &mut IFrames,
+        ),
         Without<Button>,
     >,
     q_enemies: Query<Entity, (With<Enemy>, Without<Button>, Without<Player>)>,
@@ -453,6 +538,7 @@ fn click_play_again_button(
             Without<Enemy>,
         ),
     >,
+    q_props: Query<Entity, Or<(With<Prop>, With<Pickup>, With<Bomb>, With<ExplosionVisual>)>>,
     mut pause: ResMut<ActionPauseState>,
     mut spawning: ResMut<SpawnInfo>,
     mut commands: Commands,
@@ -460,14 +546,28 @@ fn click_play_again_button(
     for (button_entity, interaction, mut color) in &mut interaction_query {
         match *interaction {
             Interaction::Pressed => {
-                let (mut player, mut transform, mut health, mut experience) = q_player.single_mut();
+                let (
+                    player_entity,
+                    mut player,
+                    mut transform,
+                    mut health,
+                    mut experience,
+                    mut status_effects,
+                    mut iframes,
+                ) = q_player.single_mut();
                 player.abilities = vec![];
+                player.buffs = vec![];
                 transform.translation.x = 0.;
                 transform.translation.y = 0.;
                 health.max = 3;
                 health.value = 3;
+                health.shield = None;
+                health.regeneration = 0.;
                 experience.curr_experience = 0;
                 experience.threshold = 20;
+                status_effects.clear();
+                iframes.clear();
+                commands.entity(player_entity).remove::<Dead>();
 
                 for enemy in q_enemies.iter() {
                     commands.entity(enemy).despawn();
@@ -485,6 +585,10 @@ fn click_play_again_button(
                     commands.entity(xp).despawn();
                 }
 
+                for prop in q_props.iter() {
+                    commands.entity(prop).despawn_recursive();
+                }
+
                 for text in q_node.iter() {
                     commands.entity(text).despawn();
                 }
@@ -2,7 +2,7 @@ use bevy::prelude::*;
 
 use crate::loading::AbilityTextures;
 
-#[derive(PartialEq, Eq, Hash, Clone, Copy)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
 pub enum Ability {
     BigBullets,
     BiggestBullets,
@@ -24,6 +24,14 @@ pub enum Ability {
     Thorns,
     TripleBarrel,
     Potion,
+    KeenEye,
+    Deadeye,
+    Ricochet,
+    Seeker,
+    ExplosiveRounds,
+    Shield,
+    Regeneration,
+    VenomousBullets,
 }
 
 impl Ability {
@@ -40,7 +48,7 @@ impl Ability {
             Self::MediumBullets,
             Self::MegaShotgun,
             Self::Reload,
-            //Self::Shells, Don't know what to do
+            Self::Shells,
             Self::ShootingSpeed,
             Self::Shotgun,
             Self::Sixfold,
@@ -49,6 +57,14 @@ impl Ability {
             Self::TripleBarrel,
             Self::MaxHp,
             Self::Potion,
+            Self::KeenEye,
+            Self::Deadeye,
+            Self::Ricochet,
+            Self::Seeker,
+            Self::ExplosiveRounds,
+            Self::Shield,
+            Self::Regeneration,
+            Self::VenomousBullets,
         ]
     }
 
@@ -74,6 +90,88 @@ impl Ability {
             Ability::Thorns => textures.thorns.clone(),
             Ability::MaxHp => textures.max_hp.clone(),
             Ability::Potion => textures.potion.clone(),
+            // Share icons until they get their own
+            Ability::KeenEye => textures.sniper.clone(),
+            Ability::Deadeye => textures.crossbow.clone(),
+            Ability::Ricochet => textures.sixfold.clone(),
+            Ability::Seeker => textures.faster.clone(),
+            Ability::ExplosiveRounds => textures.hotter_fire.clone(),
+            Ability::Shield => textures.max_hp.clone(),
+            Ability::Regeneration => textures.potion.clone(),
+            Ability::VenomousBullets => textures.flaming_bullets.clone(),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Ability::BigBullets => "Big Bullets",
+            Ability::BiggestBullets => "Biggest Bullets",
+            Ability::BulletsGalore => "Bullets Galore",
+            Ability::Crossbow => "Crossbow",
+            Ability::DoubleBarrel => "Double Barrel",
+            Ability::Faster => "Faster",
+            Ability::FlamingBullets => "Flaming Bullets",
+            Ability::HotterFire => "Hotter Fire",
+            Ability::MaxHp => "Max HP",
+            Ability::MediumBullets => "Medium Bullets",
+            Ability::MegaShotgun => "Mega Shotgun",
+            Ability::Reload => "Reload",
+            Ability::Shells => "Shells",
+            Ability::ShootingSpeed => "Shooting Speed",
+            Ability::Shotgun => "Shotgun",
+            Ability::Sixfold => "Sixfold",
+            Ability::Sniper => "Sniper",
+            Ability::Thorns => "Thorns",
+            Ability::TripleBarrel => "Triple Barrel",
+            Ability::Potion => "Potion",
+            Ability::KeenEye => "Keen Eye",
+            Ability::Deadeye => "Deadeye",
+            Ability::Ricochet => "Ricochet",
+            Ability::Seeker => "Seeker",
+            Ability::ExplosiveRounds => "Explosive Rounds",
+            Ability::Shield => "Shield",
+            Ability::Regeneration => "Regeneration",
+            Ability::VenomousBullets => "Venomous Bullets",
+        }
+    }
+
+    // Shown under the icon when choosing an ability, several abilities share icons
+    pub fn description(&self) -> &'static str {
+        match self {
+            Ability::BigBullets => "Bigger bullets hit and push harder, but fly and fire slower",
+            Ability::BiggestBullets => {
+                "The biggest bullets hit and push harder, but fly and fire slower"
+            }
+            Ability::BulletsGalore => "Three more bullets in the magazine",
+            Ability::Crossbow => "Bullets pierce through every enemy",
+            Ability::DoubleBarrel => "Fires two bullets at once, but slower",
+            Ability::Faster => "Move faster",
+            Ability::FlamingBullets => "Bullets set enemies on fire",
+            Ability::HotterFire => "Fire burns hotter",
+            Ability::MaxHp => "One more heart",
+            Ability::MediumBullets => "Bigger bullets hit and push harder, but fire slower",
+            Ability::MegaShotgun => "The shotgun fires seven bullets",
+            Ability::Reload => "Reload faster",
+            Ability::Shells => "Reload one shell at a time and shoot in between",
+            Ability::ShootingSpeed => "Shoot faster",
+            Ability::Shotgun => "Fires a spread of bullets",
+            Ability::Sixfold => "The last bullet of the magazine fires six more in every direction",
+            Ability::Sniper => "Bullets fly twice as fast",
+            Ability::Thorns => "Enemies that touch you get hurt",
+            Ability::TripleBarrel => "Fires three bullets at once",
+            Ability::Potion => "Heals two hearts",
+            Ability::KeenEye => "Higher chance of critical hits",
+            Ability::Deadeye => "Critical hits deal more damage",
+            Ability::Ricochet => {
+                "Bullets bounce once more, from the second one on they seek the next enemy"
+            }
+            Ability::Seeker => "Bullets home in on nearby enemies",
+            Ability::ExplosiveRounds => "Bullets explode on impact",
+            Ability::Shield => {
+                "A shield that blocks damage and recharges, more of it with every pick"
+            }
+            Ability::Regeneration => "Slowly regenerate health",
+            Ability::VenomousBullets => "Bullets poison enemies, every hit makes it worse",
         }
     }
 
@@ -111,9 +209,44 @@ impl Ability {
             Ability::Reload => true,
             Ability::ShootingSpeed => true,
             Ability::Sixfold => !player_abilities.contains(&Ability::Sixfold),
-            Ability::Thorns => !player_abilities.contains(&Ability::Thorns),
+            Ability::Thorns => {
+                player_abilities
+                    .iter()
+                    .filter(|ability| ability == &&Ability::Thorns)
+                    .count()
+                    < 3
+            }
             Ability::MaxHp => true,
             Ability::Potion => true,
+            Ability::KeenEye => true,
+            Ability::Deadeye => {
+                !player_abilities.contains(&Ability::Deadeye)
+                    && player_abilities.contains(&Ability::KeenEye)
+            }
+            Ability::Ricochet => {
+                player_abilities
+                    .iter()
+                    .filter(|ability| ability == &&Ability::Ricochet)
+                    .count()
+                    < 3
+            }
+            Ability::Seeker => !player_abilities.contains(&Ability::Seeker),
+            Ability::ExplosiveRounds => !player_abilities.contains(&Ability::ExplosiveRounds),
+            Ability::Shield => {
+                player_abilities
+                    .iter()
+                    .filter(|ability| ability == &&Ability::Shield)
+                    .count()
+                    < 3
+            }
+            Ability::Regeneration => {
+                player_abilities
+                    .iter()
+                    .filter(|ability| ability == &&Ability::Regeneration)
+                    .count()
+                    < 2
+            }
+            Ability::VenomousBullets => !player_abilities.contains(&Ability::VenomousBullets),
         }
     }
 
@@ -126,6 +259,20 @@ impl Ability {
         }
     }
 
+    pub fn crit_chance_bonus(&self) -> f32 {
+        match self {
+            Ability::KeenEye => 0.1,
+            _ => 0.,
+        }
+    }
+
+    pub fn crit_mult_bonus(&self) -> f32 {
+        match self {
+            Ability::Deadeye => 0.75,
+            _ => 0.,
+        }
+    }
+
     pub fn knockback_mult(&self) -> f32 {
         match self {
             Ability::MediumBullets => 1.5,
@@ -0,0 +1,77 @@
+use bevy::prelude::*;
+
+use crate::movement::pause::ActionPauseState;
+
+use super::Player;
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum BuffKind {
+    // Shoots faster
+    Frenzy,
+    // Hits harder
+    Might,
+}
+
+impl BuffKind {
+    pub fn all() -> Vec<BuffKind> {
+        vec![BuffKind::Frenzy, BuffKind::Might]
+    }
+
+    pub fn damage_mult(&self) -> f32 {
+        match self {
+            BuffKind::Might => 1.5,
+            _ => 1.,
+        }
+    }
+
+    pub fn shoot_speed_mult(&self) -> f32 {
+        match self {
+            BuffKind::Frenzy => 1.5,
+            _ => 1.,
+        }
+    }
+}
+
+/*
+Temporary boost to the player's stats, picking up one that's already active refreshes it
+*/
+pub struct Buff {
+    pub kind: BuffKind,
+    timer: Timer,
+}
+
+impl Buff {
+    pub fn new(kind: BuffKind, duration: f32) -> Buff {
+        Buff {
+            kind,
+            timer: Timer::from_seconds(duration, TimerMode::Once),
+        }
+    }
+}
+
+impl Player {
+    pub fn add_buff(&mut self, buff: Buff) {
+        self.buffs.retain(|active| active.kind != buff.kind);
+        self.buffs.push(buff);
+    }
+}
+
+pub fn update_buffs(
+    mut q_player: Query<&mut Player>,
+    pause: Res<ActionPauseState>,
+    time: Res<Time>,
+) {
+    if pause.is_paused {
+        return;
+    }
+
+    let mut player = q_player.single_mut();
+    if player.buffs.is_empty() {
+        return;
+    }
+
+    for buff in player.buffs.iter_mut() {
+        buff.timer.tick(time.delta());
+    }
+    player.buffs.retain(|buff| !buff.timer.finished());
+}
@@ -5,15 +5,21 @@ use crate::{
         info::{AnimationInfoBuilder, AnimationStateInfo},
         make_animation_bundle, Animation, AnimationStateChangeEvent, AnimationStateStorage,
     },
-    constants::SortingLayers,
+    constants::{SortingLayers, SCALING_VEC3},
     loading::TextureAssets,
 };
 
 use super::Player;
 
+// Loaded shells slide in from the side and pop into place
+const LOAD_ANIMATION_TIME: f32 = 0.15;
+const LOAD_SLIDE_DISTANCE: f32 = 16.;
+const LOAD_POP_SCALE: f32 = 0.5;
+
 #[derive(Component)]
 pub struct BulletUISprite {
     index: u32,
+    load_animation: Option<Timer>,
 }
 
 #[derive(PartialEq, Eq, Hash, Clone, Copy)]
@@ -39,7 +45,12 @@ pub struct BulletUICount(pub u32);
 pub fn manage_bullet_ui_sprites(
     q_player: Query<&Player, Without<BulletUISprite>>,
     mut q_bullets: Query<
-        (Entity, &BulletUISprite, &TextureAtlasSprite, &mut Transform),
+        (
+            Entity,
+            &mut BulletUISprite,
+            &TextureAtlasSprite,
+            &mut Transform,
+        ),
         Without<Player>,
     >,
     q_windows: Query<&Window, With<PrimaryWindow>>,
@@ -48,6 +59,7 @@ pub fn manage_bullet_ui_sprites(
     textures: Res<TextureAssets>,
     mut texture_atlases: ResMut<Assets<TextureAtlas>>,
     mut bullet_count: ResMut<BulletUICount>,
+    time: Res<Time>,
     mut commands: Commands,
 ) {
     let player = q_player.single();
@@ -65,7 +77,7 @@ pub fn manage_bullet_ui_sprites(
         bullet_count.0 += 1;
     }
 
-    for (entity, bullet, atlas, mut transform) in q_bullets.iter_mut() {
+    for (entity, mut bullet, atlas, mut transform) in q_bullets.iter_mut() {
         if bullet.index >= player.max_bullets {
             commands.entity(entity).despawn();
             continue;
@@ -80,14 +92,28 @@ pub fn manage_bullet_ui_sprites(
             animation_state_change.send(AnimationStateChangeEvent {
                 id: entity,
                 state_id: BulletUIAnimation::Available,
-            })
+            });
+            bullet.load_animation = Some(Timer::from_seconds(LOAD_ANIMATION_TIME, TimerMode::Once));
+        }
+
+        // 0 when the shell was just loaded, 1 once it is in place
+        let loaded = match bullet.load_animation.as_mut() {
+            Some(timer) => {
+                timer.tick(time.delta());
+                timer.percent()
+            }
+            None => 1.,
+        };
+        if bullet.load_animation.as_ref().is_some_and(Timer::finished) {
+            bullet.load_animation = None;
         }
 
         transform.translation = Vec3 {
-            x: window.width() / 2. - 40.,
+            x: window.width() / 2. - 40. + LOAD_SLIDE_DISTANCE * (1. - loaded),
             y: window.height() / 2. - 30. - 20. * (player.max_bullets - 1 - bullet.index) as f32,
             z: SortingLayers::UI.into(),
-        }
+        };
+        transform.scale = SCALING_VEC3 * (1. + LOAD_POP_SCALE * (1. - loaded));
     }
 }
 
@@ -109,7 +135,10 @@ fn spawn_bullet_ui_sprite(
     let texture_atlas_handle = texture_atlases.add(texture_atlas);
 
     commands
-        .spawn(BulletUISprite { index })
+        .spawn(BulletUISprite {
+            index,
+            load_animation: None,
+        })
         .insert(make_animation_bundle(
             BulletUIAnimation::Available,
             animations,
@@ -12,11 +12,20 @@ use crate::{
 
 use super::Player;
 
+const SHIELD_PIP_COLOR: Color = Color::rgb(0.5, 0.8, 1.);
+const SHIELD_PIP_SCALE: f32 = 0.75;
+
 #[derive(Component)]
 pub struct HealthUISprite {
     index: u32,
 }
 
+// Shield pips share the heart sprite but are tinted, smaller and come after the hearts
+#[derive(Component)]
+pub struct ShieldUISprite {
+    index: u32,
+}
+
 #[derive(PartialEq, Eq, Hash, Clone, Copy)]
 pub enum HealthUIAnimationState {
     Available,
@@ -37,6 +46,9 @@ pub type HealthUIAnimations = AnimationStateStorage<HealthUIAnimationState>;
 #[derive(Resource)]
 pub struct HealthUICount(pub u32);
 
+#[derive(Resource)]
+pub struct ShieldUICount(pub u32);
+
 pub fn manage_health_ui_sprites(
     q_player: Query<&Health, (With<Player>, Without<HealthUISprite>)>,
     mut q_hearts: Query<
@@ -92,6 +104,85 @@ pub fn manage_health_ui_sprites(
     }
 }
 
+pub fn manage_shield_ui_sprites(
+    q_player: Query<&Health, (With<Player>, Without<ShieldUISprite>)>,
+    mut q_pips: Query<
+        (
+            Entity,
+            &ShieldUISprite,
+            &mut TextureAtlasSprite,
+            &mut Transform,
+        ),
+        Without<Player>,
+    >,
+    q_windows: Query<&Window, With<PrimaryWindow>>,
+    mut animation_state_change: EventWriter<AnimationStateChangeEvent<HealthUIAnimationState>>,
+    animations: Res<HealthUIAnimations>,
+    textures: Res<TextureAssets>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    mut pip_count: ResMut<ShieldUICount>,
+    mut commands: Commands,
+) {
+    let health = q_player.single();
+    let window = q_windows.single();
+    let (shield, max_shield) = (health.shield_value(), health.shield_max());
+
+    while pip_count.0 < max_shield {
+        let texture_atlas = TextureAtlas::from_grid(
+            textures.heart_ui.clone(),
+            Vec2 { x: 16., y: 16. },
+            2,
+            1,
+            None,
+            None,
+        );
+        let texture_atlas_handle = texture_atlases.add(texture_atlas);
+
+        commands
+            .spawn(ShieldUISprite { index: pip_count.0 })
+            .insert(make_animation_bundle(
+                HealthUIAnimationState::Available,
+                &animations,
+                texture_atlas_handle,
+                Vec3 {
+                    x: 10000.,
+                    y: 10000.,
+                    z: SortingLayers::UIBack.into(),
+                },
+                SHIELD_PIP_SCALE,
+            ));
+
+        pip_count.0 += 1;
+    }
+
+    for (entity, pip, mut atlas, mut transform) in q_pips.iter_mut() {
+        if pip.index >= max_shield {
+            commands.entity(entity).despawn();
+            pip_count.0 = pip_count.0.min(pip.index);
+            continue;
+        }
+
+        if atlas.index == 0 && pip.index >= shield {
+            animation_state_change.send(AnimationStateChangeEvent {
+                id: entity,
+                state_id: HealthUIAnimationState::Unavailable,
+            })
+        } else if atlas.index == 1 && pip.index < shield {
+            animation_state_change.send(AnimationStateChangeEvent {
+                id: entity,
+                state_id: HealthUIAnimationState::Available,
+            })
+        }
+
+        atlas.color = SHIELD_PIP_COLOR;
+        transform.translation = Vec3 {
+            x: -window.width() / 2. + 40. + 40. * health.max as f32 + 30. * pip.index as f32,
+            y: window.height() / 2. - 30.,
+            z: 5.,
+        };
+    }
+}
+
 fn spawn_heart_ui_sprite(
     animations: &Res<HealthUIAnimations>,
     textures: &Res<TextureAssets>,
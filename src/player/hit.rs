@@ -1,7 +1,7 @@
 use bevy::prelude::*;
 
 use crate::{
-    combat::health::{Health, TookDamageEvent},
+    combat::{health::TookDamageEvent, iframes::IFrames},
     constants::{SortingLayers, SCALING_VEC3},
     loading::TextureAssets,
 };
@@ -13,14 +13,14 @@ pub struct HitSprite(Timer);
 
 pub fn update_hit_sprite(
     mut q_hit: Query<(Entity, &mut HitSprite, &mut Visibility, &mut Transform), Without<Player>>,
-    q_player: Query<(Entity, &Health, &Transform), With<Player>>,
+    q_player: Query<(Entity, &IFrames, &Transform), With<Player>>,
     mut ev_dmgs: EventReader<TookDamageEvent>,
     time: Res<Time>,
 ) {
-    let (player, p_health, p_transform) = q_player.single();
+    let (player, p_iframes, p_transform) = q_player.single();
     let (_entity, mut hit, mut visibility, mut transform) = q_hit.single_mut();
 
-    if !p_health.is_invincible {
+    if !p_iframes.is_active() {
         *visibility = Visibility::Hidden;
         return;
     }
@@ -1,12 +1,10 @@
 use std::time::Duration;
 
 use bevy::prelude::*;
-use bevy_kira_audio::AudioControl;
 
 use crate::{
-    audio::FXChannel,
     constants::{SortingLayers, SCALING_VEC3},
-    loading::{AudioAssets, TextureAssets},
+    loading::TextureAssets,
 };
 
 use super::Player;
@@ -50,8 +48,6 @@ pub fn update_reload_ui(
     mut q_reload_ui: Query<(&mut Transform, &mut TextureAtlasSprite), With<ReloadUI>>,
     q_windows: Query<&Window, Without<ReloadUI>>,
     q_player: Query<&Player, (Without<ReloadUI>, Without<Window>)>,
-    audio_assets: Res<AudioAssets>,
-    fx_channel: Res<FXChannel>,
     timer: Res<ReloadTimer>,
 ) {
     let (mut reload_transform, mut reload_atlas) = q_reload_ui.single_mut();
@@ -64,14 +60,11 @@ pub fn update_reload_ui(
         z: SortingLayers::UI.into(),
     };
 
+    // With shells this shows the progress of the shell being loaded
     if timer.0.remaining() > Duration::ZERO {
-        let index = 9.0 * (1.0 - timer.0.remaining().as_secs_f32() / player.reload_time());
+        let index = 9.0 * timer.0.percent();
         reload_atlas.index = index as usize;
     } else {
         reload_atlas.index = 9;
     }
-
-    if timer.0.just_finished() {
-        fx_channel.play(audio_assets.reload_done.clone());
-    }
 }
@@ -7,27 +7,39 @@ use bevy_kira_audio::AudioControl;
 
 use crate::{
     audio::FXChannel,
-    collision::collider::Collider,
+    collision::{
+        collider::{Collider, ContinuousCollision},
+        layers::CollisionLayer,
+    },
     combat::{
-        health::HealthType,
+        bounce::Bounces,
+        damage::DamageInfo,
+        explosion::{Explodes, Explosion},
+        homing::Homing,
         knockback::Knockback,
-        projectile::{DamageTarget, PiercingMode, Projectile},
+        projectile::{DamageTarget, EdgeDespawns, MaxRange, PiercingMode, Projectile},
         teams::Team,
     },
     constants::{SortingLayers, SCALING_VEC3},
     loading::{AudioAssets, TextureAssets},
     movement::{pause::ActionPauseState, velocity::Velocity},
-    util::radians::Radian,
+    util::{crit_rng::CritRNG, radians::Radian, rng::RNG},
 };
 
 use super::{ability::Ability, reload_ui::ReloadTimer, Player};
 
+const BULLET_RANGE: f32 = 1500.;
+const SEEKER_TURN_RATE: f32 = 4.;
+const SEEKER_RADIUS: f32 = 250.;
+// Explosive rounds deal half of the bullet's damage around where they land
+const EXPLOSIVE_ROUNDS_RADIUS: f32 = 40.;
+
 #[derive(Resource)]
 pub struct ShootingCooldown(pub Timer);
 
 pub fn shoot(
     buttons: Res<Input<MouseButton>>,
-    mut q_player: Query<(&mut Player, &Transform)>,
+    mut q_player: Query<(Entity, &mut Player, &Transform)>,
     q_windows: Query<&Window, With<PrimaryWindow>>,
     mut shooting_cooldown: ResMut<ShootingCooldown>,
     mut reload_timer: ResMut<ReloadTimer>,
@@ -36,6 +48,7 @@ pub fn shoot(
     fx_channel: Res<FXChannel>,
     time: Res<Time>,
     pause: Res<ActionPauseState>,
+    mut crit_rng: ResMut<CritRNG>,
     mut commands: Commands,
 ) {
     if pause.is_paused {
@@ -46,16 +59,49 @@ pub fn shoot(
     reload_timer.0.tick(time.delta());
 
     let window = q_windows.single();
-    let (mut player, transform) = q_player.single_mut();
+    let (player_entity, mut player, transform) = q_player.single_mut();
+
+    let has_shells = player.abilities.contains(&Ability::Shells);
+
+    if reload_timer.0.just_finished() && player.is_reloading {
+        if has_shells {
+            player.curr_bullets = (player.curr_bullets + 1).min(player.max_bullets);
+        } else {
+            player.curr_bullets = player.max_bullets;
+        }
+
+        if player.curr_bullets < player.max_bullets {
+            // Next shell
+            reload_timer.0.reset();
+        } else {
+            player.is_reloading = false;
+        }
 
-    if reload_timer.0.just_finished() {
-        player.curr_bullets = player.max_bullets;
-        player.is_reloading = false;
+        fx_channel.play(audio_assets.reload_done.clone());
     }
 
-    if shooting_cooldown.0.finished() && !player.is_reloading && buttons.pressed(MouseButton::Left)
+    // Shells are loaded whenever the player stops shooting
+    if has_shells
+        && !player.is_reloading
+        && player.curr_bullets < player.max_bullets
+        && shooting_cooldown.0.finished()
+        && !buttons.pressed(MouseButton::Left)
     {
+        start_reload(&mut player, &mut reload_timer);
+        fx_channel.play(audio_assets.reload.clone());
+    }
+
+    // Loading shells can be interrupted by shooting the ones already loaded
+    let can_shoot = !player.is_reloading || (has_shells && player.curr_bullets > 0);
+
+    if shooting_cooldown.0.finished() && can_shoot && buttons.pressed(MouseButton::Left) {
         if let Some(cursor_position) = window.cursor_position() {
+            if player.is_reloading {
+                player.is_reloading = false;
+                let duration = reload_timer.0.duration();
+                reload_timer.0.set_elapsed(duration);
+            }
+
             // Reset cooldown
             shooting_cooldown
                 .0
@@ -82,7 +128,7 @@ pub fn shoot(
                 } * 10.
                 + Vec3::Z * 5.;
 
-            let dmg = player.damage();
+            let dmg = DamageInfo::physical(player.damage()).with_source(player_entity);
             let knockback = player.knockback();
             let mut velocity: f32 = 500.;
 
@@ -100,11 +146,7 @@ pub fn shoot(
 
             player.curr_bullets -= 1;
             if player.curr_bullets == 0 {
-                player.is_reloading = true;
-                reload_timer
-                    .0
-                    .set_duration(Duration::from_secs_f32(player.reload_time()));
-                reload_timer.0.reset();
+                start_reload(&mut player, &mut reload_timer);
 
                 fx_channel.play(audio_assets.reload.clone());
 
@@ -118,6 +160,7 @@ pub fn shoot(
                             (Radian::FULL / 6. * i as f32).normalize().unit_vector(),
                             velocity,
                             dmg,
+                            &mut crit_rng.0,
                             knockback,
                         );
                     }
@@ -145,6 +188,7 @@ pub fn shoot(
                             .unit_vector(),
                         velocity,
                         dmg,
+                        &mut crit_rng.0,
                         knockback,
                     );
                     spawn_bullet(
@@ -157,6 +201,7 @@ pub fn shoot(
                             .unit_vector(),
                         velocity,
                         dmg,
+                        &mut crit_rng.0,
                         knockback,
                     );
                 }
@@ -175,6 +220,7 @@ pub fn shoot(
                             .unit_vector(),
                         velocity,
                         dmg,
+                        &mut crit_rng.0,
                         knockback,
                     );
                 }
@@ -189,6 +235,7 @@ pub fn shoot(
                     (angle_to_target + offset_angle).normalize().unit_vector(),
                     velocity,
                     dmg,
+                    &mut crit_rng.0,
                     knockback,
                 );
                 spawn_bullet(
@@ -199,6 +246,7 @@ pub fn shoot(
                     (angle_to_target).unit_vector(),
                     velocity,
                     dmg,
+                    &mut crit_rng.0,
                     knockback,
                 );
                 spawn_bullet(
@@ -209,6 +257,7 @@ pub fn shoot(
                     (angle_to_target - offset_angle).normalize().unit_vector(),
                     velocity,
                     dmg,
+                    &mut crit_rng.0,
                     knockback,
                 );
             } else if player.abilities.contains(&Ability::DoubleBarrel) {
@@ -226,6 +275,7 @@ pub fn shoot(
                     direction_vec,
                     velocity,
                     dmg,
+                    &mut crit_rng.0,
                     knockback,
                 );
                 spawn_bullet(
@@ -236,6 +286,7 @@ pub fn shoot(
                     direction_vec,
                     velocity,
                     dmg,
+                    &mut crit_rng.0,
                     knockback,
                 );
             } else {
@@ -247,6 +298,7 @@ pub fn shoot(
                     direction_vec,
                     velocity,
                     dmg,
+                    &mut crit_rng.0,
                     knockback,
                 );
             }
@@ -256,6 +308,19 @@ pub fn shoot(
     }
 }
 
+fn start_reload(player: &mut Player, reload_timer: &mut ReloadTimer) {
+    let reload_time = match player.abilities.contains(&Ability::Shells) {
+        true => player.shell_reload_time(),
+        false => player.reload_time(),
+    };
+
+    player.is_reloading = true;
+    reload_timer
+        .0
+        .set_duration(Duration::from_secs_f32(reload_time));
+    reload_timer.0.reset();
+}
+
 fn spawn_bullet(
     player: &Player,
     commands: &mut Commands,
@@ -263,24 +328,28 @@ fn spawn_bullet(
     textures: &Res<TextureAssets>,
     direction_vec: Vec2,
     velocity: f32,
-    damage: HealthType,
+    damage: DamageInfo,
+    crit_rng: &mut RNG,
     knockback: f32,
 ) {
-    commands
-        .spawn(SpriteBundle {
-            texture: match player.abilities.contains(&Ability::BigBullets) {
-                true => textures.bullet_medium.clone(),
-                false => textures.bullet_small.clone(),
-            },
-            transform: Transform {
-                translation,
-                scale: SCALING_VEC3,
-                rotation: Quat::IDENTITY,
-            },
-            ..Default::default()
-        })
+    let damage = player.roll_crit(damage, crit_rng);
+
+    let mut bullet = commands.spawn(SpriteBundle {
+        texture: match player.abilities.contains(&Ability::BigBullets) {
+            true => textures.bullet_medium.clone(),
+            false => textures.bullet_small.clone(),
+        },
+        transform: Transform {
+            translation,
+            scale: SCALING_VEC3,
+            rotation: Quat::IDENTITY,
+        },
+        ..Default::default()
+    });
+
+    bullet
         .insert(Projectile {
-            dmg: damage,
+            damage,
             damage_target: DamageTarget::Team(Team::Enemy),
             piercing_mode: match player.abilities.contains(&Ability::Crossbow) {
                 true => PiercingMode::All,
@@ -292,6 +361,36 @@ fn spawn_bullet(
         .insert(Velocity {
             vec: direction_vec * velocity,
         })
-        .insert(Collider::new_circle(5., translation.truncate()))
-        .insert(Knockback { force: knockback });
+        .insert(Collider::new_circle(5.).with_layer(CollisionLayer::PlayerProjectile))
+        .insert(ContinuousCollision::default())
+        .insert(Knockback { force: knockback })
+        .insert(MaxRange::new(BULLET_RANGE))
+        .insert(EdgeDespawns);
+
+    let bounces = player.bounces();
+    if bounces > 0 {
+        bullet.insert(match player.bounces_retarget() {
+            true => Bounces::new(bounces).with_retarget(),
+            false => Bounces::new(bounces),
+        });
+    }
+
+    if player.abilities.contains(&Ability::ExplosiveRounds) {
+        let explosion = Explosion::new(
+            EXPLOSIVE_ROUNDS_RADIUS,
+            DamageInfo {
+                amount: (damage.amount / 2).max(1),
+                ..damage
+            }
+            .with_ability(Ability::ExplosiveRounds),
+            DamageTarget::Team(Team::Enemy),
+        )
+        .with_knockback(knockback);
+
+        bullet.insert(Explodes::new(explosion).on_hit().on_expiry());
+    }
+
+    if player.abilities.contains(&Ability::Seeker) {
+        bullet.insert(Homing::new(SEEKER_TURN_RATE, SEEKER_RADIUS, Team::Enemy));
+    }
 }
@@ -0,0 +1,136 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{
+    collision::collider::IsCollidingEvent,
+    combat::{
+        damage::{DamageInfo, DamageType, Resistances},
+        health::{Dead, Health, HealthType, TookDamageEvent},
+        status_effect::StatusEffects,
+    },
+    constants::{SortingLayers, SCALING_VEC3},
+    enemies::enemy::Enemy,
+    loading::TextureAssets,
+    movement::pause::ActionPauseState,
+    palette::Palette,
+};
+
+use super::{ability::Ability, Player};
+
+const THORNS_DAMAGE: HealthType = 3;
+// How long an enemy is safe from thorns after being pricked
+const THORNS_COOLDOWN: f32 = 0.5;
+const BURST_DURATION: f32 = 0.3;
+
+#[derive(Component)]
+pub struct ThornsBurst(Timer);
+
+// Enemies touching the player take damage for every thorns the player has
+pub fn thorns(
+    q_player: Query<(Entity, &Player, &Transform)>,
+    mut q_enemies: Query<
+        (
+            &mut Health,
+            &Transform,
+            Option<&Resistances>,
+            Option<&StatusEffects>,
+        ),
+        (With<Enemy>, Without<Player>, Without<Dead>),
+    >,
+    mut collisions: EventReader<IsCollidingEvent>,
+    mut ev_dmg: EventWriter<TookDamageEvent>,
+    mut cooldowns: Local<HashMap<Entity, Timer>>,
+    textures: Res<TextureAssets>,
+    palette: Res<Palette>,
+    pause: Res<ActionPauseState>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    if pause.is_paused {
+        return;
+    }
+
+    for cooldown in cooldowns.values_mut() {
+        cooldown.tick(time.delta());
+    }
+    cooldowns.retain(|_, cooldown| !cooldown.finished());
+
+    let (player_entity, player, player_transform) = q_player.single();
+    let stacks = player
+        .abilities
+        .iter()
+        .filter(|ability| ability == &&Ability::Thorns)
+        .count() as HealthType;
+
+    if stacks == 0 {
+        return;
+    }
+
+    for ev in collisions.iter() {
+        let enemy = match (ev.collision.entity_a, ev.collision.entity_b) {
+            (a, b) if a == player_entity => b,
+            (a, b) if b == player_entity => a,
+            _ => continue,
+        };
+
+        if cooldowns.contains_key(&enemy) {
+            continue;
+        }
+
+        let Ok((mut health, enemy_transform, resistances, status_effects)) =
+            q_enemies.get_mut(enemy)
+        else {
+            continue;
+        };
+
+        let damage = DamageInfo::new(THORNS_DAMAGE * stacks, DamageType::Thorns)
+            .with_source(player_entity)
+            .with_ability(Ability::Thorns)
+            .as_tick();
+        health.take_damage(enemy, &mut ev_dmg, damage, resistances, status_effects);
+        cooldowns.insert(enemy, Timer::from_seconds(THORNS_COOLDOWN, TimerMode::Once));
+
+        // Burst halfway between the player and the enemy
+        let contact = player_transform
+            .translation
+            .truncate()
+            .lerp(enemy_transform.translation.truncate(), 0.5);
+
+        commands
+            .spawn(SpriteBundle {
+                texture: textures.hit.clone(),
+                sprite: Sprite {
+                    color: palette.red,
+                    ..default()
+                },
+                transform: Transform {
+                    translation: contact.extend(SortingLayers::Front.into()),
+                    scale: SCALING_VEC3 * 0.5,
+                    ..default()
+                },
+                ..default()
+            })
+            .insert(ThornsBurst(Timer::from_seconds(
+                BURST_DURATION,
+                TimerMode::Once,
+            )));
+    }
+}
+
+// Bursts grow and fade out
+pub fn update_thorns_bursts(
+    mut q_bursts: Query<(Entity, &mut ThornsBurst, &mut Transform, &mut Sprite)>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (entity, mut burst, mut transform, mut sprite) in q_bursts.iter_mut() {
+        burst.0.tick(time.delta());
+
+        if burst.0.finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        transform.scale = SCALING_VEC3 * (0.5 + burst.0.percent());
+        sprite.color.set_a(burst.0.percent_left());
+    }
+}
@@ -58,7 +58,7 @@ pub fn update_selection_groups(
 
                 if !cursor_point.x.is_nan()
                     && !cursor_point.y.is_nan()
-                    && collider.contains_point(transform.translation.truncate(), cursor_point)
+                    && collider.contains_point(transform.into(), cursor_point)
                 {
                     if group.hovered_index != element.index {
                         unhover.send(UnhoverEvent {
@@ -2,8 +2,9 @@ use bevy::prelude::*;
 
 use crate::GameState;
 
-use self::{pitch_rng::spawn_pitch_rng, rng::GlobalSeed};
+use self::{crit_rng::spawn_crit_rng, pitch_rng::spawn_pitch_rng, rng::GlobalSeed};
 
+pub mod crit_rng;
 pub mod pitch_rng;
 pub mod radians;
 pub mod rng;
@@ -12,7 +13,10 @@ pub struct UtilPlugin;
 
 impl Plugin for UtilPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
-        app.add_systems(OnEnter(GameState::Playing), spawn_pitch_rng)
-            .insert_resource(GlobalSeed("test".into()));
+        app.add_systems(
+            OnEnter(GameState::Playing),
+            (spawn_pitch_rng, spawn_crit_rng),
+        )
+        .insert_resource(GlobalSeed("test".into()));
     }
 }
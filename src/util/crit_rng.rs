@@ -0,0 +1,10 @@
+use bevy::prelude::*;
+
+use super::rng::{GlobalSeed, RNG};
+
+#[derive(Resource)]
+pub struct CritRNG(pub RNG);
+
+pub fn spawn_crit_rng(seed: Res<GlobalSeed>, mut commands: Commands) {
+    commands.insert_resource(CritRNG(RNG::new(&seed.0, "crit")))
+}